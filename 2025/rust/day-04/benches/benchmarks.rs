@@ -14,3 +14,65 @@ fn part1() {
 fn part2() {
     part2::process(divan::black_box(include_str!("../input2.txt",))).unwrap();
 }
+
+#[divan::bench]
+fn part1_bit_grid() {
+    bit_grid::process_part1(divan::black_box(include_str!("../input1.txt",))).unwrap();
+}
+
+#[divan::bench]
+fn part2_bit_grid() {
+    bit_grid::process_part2(divan::black_box(include_str!("../input2.txt",))).unwrap();
+}
+
+/// Side lengths of the square synthetic grids used to compare the backends
+const SYNTHETIC_SIZES: &[usize] = &[128, 512, 2048];
+
+/// Generates a deterministic pseudo random grid with roughly 60% paper
+fn synthetic_grid(size: usize) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut result = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        for _ in 0..size {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            result.push(if state % 10 < 6 { '@' } else { '.' });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+mod synthetic {
+    use super::*;
+
+    #[divan::bench(args = SYNTHETIC_SIZES)]
+    fn part1_cells(bencher: divan::Bencher, size: usize) {
+        bencher
+            .with_inputs(|| synthetic_grid(size))
+            .bench_refs(|input| part1::process(input).unwrap());
+    }
+
+    #[divan::bench(args = SYNTHETIC_SIZES)]
+    fn part1_bit_grid(bencher: divan::Bencher, size: usize) {
+        bencher
+            .with_inputs(|| synthetic_grid(size))
+            .bench_refs(|input| bit_grid::process_part1(input).unwrap());
+    }
+
+    #[divan::bench(args = SYNTHETIC_SIZES)]
+    fn part2_cells(bencher: divan::Bencher, size: usize) {
+        bencher
+            .with_inputs(|| synthetic_grid(size))
+            .bench_refs(|input| part2::process(input).unwrap());
+    }
+
+    #[divan::bench(args = SYNTHETIC_SIZES)]
+    fn part2_bit_grid(bencher: divan::Bencher, size: usize) {
+        bencher
+            .with_inputs(|| synthetic_grid(size))
+            .bench_refs(|input| bit_grid::process_part2(input).unwrap());
+    }
+}
@@ -0,0 +1,300 @@
+use miette::bail;
+
+use crate::neighbourhood::{MAX_OFFSETS, Rules};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Alternative storage for the paper grid that packs each row into `u64`
+/// words (bit `c % 64` of word `c / 64` is column `c`).
+///
+/// Neighbour counting is done for 64 cells at a time by shifting whole words
+/// (one shifted plane per neighbourhood offset) and adding the planes with a
+/// bit sliced adder, so there are no per cell bounds checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn parse(input: &str) -> miette::Result<Self> {
+        let lines: Vec<&str> = input.lines().collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.len());
+        let words_per_row = width.div_ceil(WORD_BITS);
+        let mut result = Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        };
+
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                bail!(
+                    "all rows are not the same length. Expected {width} but row {row} has {}",
+                    line.len()
+                );
+            }
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    '@' => result.set(row, col),
+                    '.' => {}
+                    other => bail!("unexpected cell value found: {other:?}"),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if there is paper at the given position
+    #[must_use]
+    pub fn is_paper(&self, row: usize, col: usize) -> bool {
+        debug_assert!(row < self.height && col < self.width);
+        self.row(row)[col / WORD_BITS] & (1 << (col % WORD_BITS)) != 0
+    }
+
+    fn set(&mut self, row: usize, col: usize) {
+        let start = row * self.words_per_row;
+        self.bits[start + col / WORD_BITS] |= 1 << (col % WORD_BITS);
+    }
+
+    /// Number of cells that contain paper
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        let start = row * self.words_per_row;
+        &self.bits[start..start + self.words_per_row]
+    }
+
+    /// Returns the cells containing paper that are accessible under `rules`
+    #[must_use]
+    pub fn accessible(&self, rules: &Rules) -> Self {
+        let mut result = Self {
+            bits: vec![0; self.bits.len()],
+            ..*self
+        };
+        let offsets = rules.neighbourhood().offsets();
+        let mut planes = vec![0; offsets.len()];
+
+        for row in 0..self.height {
+            let current = self.row(row);
+            // Rows each offset reads from (`None` if outside of the grid)
+            let neighbour_rows: Vec<Option<&[u64]>> = offsets
+                .iter()
+                .map(|&(row_offset, _)| {
+                    row.checked_add_signed(row_offset)
+                        .filter(|&row| row < self.height)
+                        .map(|row| self.row(row))
+                })
+                .collect();
+
+            let out_start = row * self.words_per_row;
+            for (word_idx, &current_word) in current.iter().enumerate() {
+                for ((plane, &(_, col_offset)), neighbour_row) in
+                    planes.iter_mut().zip(offsets).zip(neighbour_rows.iter())
+                {
+                    *plane = neighbour_row.map_or(0, |neighbour_row| {
+                        shifted(neighbour_row, word_idx, col_offset)
+                    });
+                }
+                let counter = BitSlicedCounter::from_planes(&planes);
+                result.bits[out_start + word_idx] =
+                    current_word & counter.less_than(rules.threshold());
+            }
+        }
+
+        result
+    }
+
+    /// Clears every cell that is set in `mask`
+    pub fn remove(&mut self, mask: &Self) {
+        debug_assert_eq!(self.width, mask.width);
+        debug_assert_eq!(self.height, mask.height);
+        for (word, mask_word) in self.bits.iter_mut().zip(mask.bits.iter()) {
+            *word &= !mask_word;
+        }
+    }
+}
+
+/// Word at `word_idx` where bit `c` holds the value of column `c +
+/// col_offset` (columns outside of the row read as 0)
+fn shifted(row: &[u64], word_idx: usize, col_offset: isize) -> u64 {
+    let word_offset = col_offset.div_euclid(WORD_BITS as isize);
+    let bit_offset = col_offset.rem_euclid(WORD_BITS as isize) as u32;
+    let word_at = |offset: isize| {
+        word_idx
+            .checked_add_signed(word_offset + offset)
+            .and_then(|idx| row.get(idx))
+            .copied()
+            .unwrap_or(0)
+    };
+    if bit_offset == 0 {
+        word_at(0)
+    } else {
+        (word_at(0) >> bit_offset) | (word_at(1) << (u64::BITS - bit_offset))
+    }
+}
+
+/// Holds one counter per bit position of a word. `digits[i]` stores bit `i`
+/// of every counter and only the first `len` digits are in use.
+struct BitSlicedCounter {
+    digits: [u64; 8],
+    len: usize,
+}
+
+impl BitSlicedCounter {
+    fn from_planes(planes: &[u64]) -> Self {
+        debug_assert!(planes.len() <= MAX_OFFSETS, "counter would overflow");
+        let len = (usize::BITS - planes.len().leading_zeros()) as usize;
+        let mut digits = [0; 8];
+        for &plane in planes {
+            // Ripple carry add of a single bit to each counter
+            let mut carry = plane;
+            for digit in digits[..len].iter_mut() {
+                let next_carry = *digit & carry;
+                *digit ^= carry;
+                carry = next_carry;
+            }
+        }
+        Self { digits, len }
+    }
+
+    /// Mask of the positions whose counter is strictly less than `value`
+    fn less_than(&self, value: usize) -> u64 {
+        if value >= 1 << self.len {
+            return u64::MAX;
+        }
+        let mut is_less = 0;
+        let mut is_equal = u64::MAX;
+        for (i, &digit) in self.digits[..self.len].iter().enumerate().rev() {
+            if value & (1 << i) != 0 {
+                is_less |= is_equal & !digit;
+                is_equal &= digit;
+            } else {
+                is_equal &= !digit;
+            }
+        }
+        is_less
+    }
+}
+
+/// Same as [`crate::part1::process`] but using [`BitGrid`]
+#[tracing::instrument]
+pub fn process_part1(input: &str) -> miette::Result<String> {
+    process_part1_with_rules(input, &Rules::default())
+}
+
+/// Same as [`crate::part1::process_with_rules`] but using [`BitGrid`]
+#[tracing::instrument]
+pub fn process_part1_with_rules(input: &str, rules: &Rules) -> miette::Result<String> {
+    let grid = BitGrid::parse(input)?;
+    Ok(grid.accessible(rules).count().to_string())
+}
+
+/// Same as [`crate::part2::process`] but using [`BitGrid`]
+#[tracing::instrument]
+pub fn process_part2(input: &str) -> miette::Result<String> {
+    process_part2_with_rules(input, &Rules::default())
+}
+
+/// Same as [`crate::part2::process_with_rules`] but using [`BitGrid`]
+#[tracing::instrument]
+pub fn process_part2_with_rules(input: &str, rules: &Rules) -> miette::Result<String> {
+    let mut result = 0;
+    let mut grid = BitGrid::parse(input)?;
+    loop {
+        let removable = grid.accessible(rules);
+        if removable.is_empty() {
+            break;
+        }
+        result += removable.count();
+        grid.remove(&removable);
+    }
+    Ok(result.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neighbourhood::Neighbourhood;
+
+    const EXAMPLE: &str = "..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.";
+
+    #[test]
+    fn test_process() -> miette::Result<()> {
+        assert_eq!(process_part1(EXAMPLE)?, "13");
+        assert_eq!(process_part2(EXAMPLE)?, "43");
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_cells_across_word_boundaries() -> miette::Result<()> {
+        // Wide enough to span several words with a width that is not a multiple
+        // of the word size
+        let input: String = (0..40)
+            .map(|row| {
+                (0..150)
+                    .map(|col| {
+                        if (row * 7 + col * 13) % 5 < 3 {
+                            '@'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(process_part1(&input)?, crate::part1::process(&input)?);
+        assert_eq!(process_part2(&input)?, crate::part2::process(&input)?);
+
+        // Offsets that reach into neighbouring words
+        let rules = Rules::new(
+            Neighbourhood::Custom(vec![(0, -70), (0, 64), (-1, 3), (2, -1), (1, 1)]),
+            2,
+        )?;
+        assert_eq!(
+            process_part1_with_rules(&input, &rules)?,
+            crate::part1::process_with_rules(&input, &rules)?
+        );
+        assert_eq!(
+            process_part2_with_rules(&input, &rules)?,
+            crate::part2::process_with_rules(&input, &rules)?
+        );
+        Ok(())
+    }
+}
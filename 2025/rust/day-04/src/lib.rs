@@ -1,2 +1,4 @@
+pub mod bit_grid;
+pub mod neighbourhood;
 pub mod part1;
 pub mod part2;
@@ -0,0 +1,118 @@
+use miette::bail;
+
+const VON_NEUMANN_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const MOORE_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// The largest number of offsets supported in a neighbourhood
+pub const MAX_OFFSETS: usize = u8::MAX as usize;
+
+/// Which cells are considered neighbours of a cell
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 4 orthogonally adjacent cells
+    VonNeumann,
+    /// All 8 surrounding cells (what the puzzle uses)
+    #[default]
+    Moore,
+    /// Arbitrary `(row, col)` offsets from the cell
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    /// The `(row, col)` offsets from a cell to each of its neighbours
+    #[must_use]
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Neighbourhood::VonNeumann => &VON_NEUMANN_OFFSETS,
+            Neighbourhood::Moore => &MOORE_OFFSETS,
+            Neighbourhood::Custom(offsets) => offsets,
+        }
+    }
+}
+
+/// Decides when a roll of paper can be accessed by a forklift
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    neighbourhood: Neighbourhood,
+    threshold: usize,
+}
+
+impl Rules {
+    /// A roll is accessible if fewer than `threshold` of its neighbours (as
+    /// defined by `neighbourhood`) contain paper
+    pub fn new(neighbourhood: Neighbourhood, threshold: usize) -> miette::Result<Self> {
+        let offsets = neighbourhood.offsets();
+        if offsets.len() > MAX_OFFSETS {
+            bail!(
+                "neighbourhood has {} offsets but at most {MAX_OFFSETS} are supported",
+                offsets.len()
+            );
+        }
+        for (i, offset) in offsets.iter().enumerate() {
+            if *offset == (0, 0) {
+                bail!("a cell cannot be its own neighbour");
+            }
+            if offsets[..i].contains(offset) {
+                bail!("duplicate offset found in neighbourhood: {offset:?}");
+            }
+        }
+        Ok(Self {
+            neighbourhood,
+            threshold,
+        })
+    }
+
+    #[must_use]
+    pub fn neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+
+    #[must_use]
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns `true` if a roll with `paper_count` neighbouring rolls can be
+    /// accessed
+    #[must_use]
+    pub fn is_accessible(&self, paper_count: usize) -> bool {
+        paper_count < self.threshold
+    }
+
+    /// Positions of the neighbours of (`row`, `col`) that are inside a grid of
+    /// the given size
+    pub fn neighbours(
+        &self,
+        row: usize,
+        col: usize,
+        row_count: usize,
+        col_count: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(move |&(row_offset, col_offset)| {
+                let row = row.checked_add_signed(row_offset)?;
+                let col = col.checked_add_signed(col_offset)?;
+                (row < row_count && col < col_count).then_some((row, col))
+            })
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Moore,
+            threshold: 4,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::neighbourhood::Rules;
+
 #[derive(Debug)]
 enum Cell {
     Paper,
@@ -26,6 +28,11 @@ impl From<char> for Cell {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    process_with_rules(input, &Rules::default())
+}
+
+#[tracing::instrument]
+pub fn process_with_rules(input: &str, rules: &Rules) -> miette::Result<String> {
     let mut result = 0;
     let mut grid: Vec<Vec<Cell>> = vec![];
 
@@ -44,7 +51,9 @@ pub fn process(input: &str) -> miette::Result<String> {
 
     for (row, row_values) in grid.iter().enumerate() {
         for (col, cell) in row_values.iter().enumerate() {
-            if cell.is_paper() && neighbouring_paper_count(row, col, &grid) < 4 {
+            if cell.is_paper()
+                && rules.is_accessible(neighbouring_paper_count(row, col, &grid, rules))
+            {
                 result += 1;
             }
         }
@@ -52,59 +61,17 @@ pub fn process(input: &str) -> miette::Result<String> {
     Ok(result.to_string())
 }
 
-fn neighbouring_paper_count(row: usize, col: usize, grid: &[Vec<Cell>]) -> u8 {
-    let mut result = 0;
-    // Upper row
-    if row > 0 {
-        // Left Up
-        if col > 0 && grid[row - 1][col - 1].is_paper() {
-            result += 1;
-        }
-
-        // Straight Up
-        if grid[row - 1][col].is_paper() {
-            result += 1;
-        }
-
-        // Right Up
-        if col < grid[0].len() - 1 && grid[row - 1][col + 1].is_paper() {
-            result += 1;
-        }
-    }
-
-    // Left
-    if col > 0 && grid[row][col - 1].is_paper() {
-        result += 1;
-    }
-
-    // Right
-    if col < grid[0].len() - 1 && grid[row][col + 1].is_paper() {
-        result += 1;
-    }
-
-    // Lower Row
-    if row < grid.len() - 1 {
-        // Left Down
-        if col > 0 && grid[row + 1][col - 1].is_paper() {
-            result += 1;
-        }
-
-        // Straight Down
-        if grid[row + 1][col].is_paper() {
-            result += 1;
-        }
-
-        // Right Down
-        if col < grid[0].len() - 1 && grid[row + 1][col + 1].is_paper() {
-            result += 1;
-        }
-    }
-    result
+fn neighbouring_paper_count(row: usize, col: usize, grid: &[Vec<Cell>], rules: &Rules) -> usize {
+    rules
+        .neighbours(row, col, grid.len(), grid[0].len())
+        .filter(|&(row, col)| grid[row][col].is_paper())
+        .count()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::neighbourhood::Neighbourhood;
 
     #[test]
     fn test_process() -> miette::Result<()> {
@@ -121,4 +88,18 @@ mod tests {
         assert_eq!(process(input)?, "13");
         Ok(())
     }
+
+    #[test]
+    fn test_process_von_neumann() -> miette::Result<()> {
+        let input = "@@@
+@@@
+.@.";
+        // Only the middle cell has 4 orthogonal neighbours
+        let rules = Rules::new(Neighbourhood::VonNeumann, 4)?;
+        assert_eq!(process_with_rules(input, &rules)?, "6");
+        // The top middle cell has 3 neighbours and the middle has 4
+        let rules = Rules::new(Neighbourhood::VonNeumann, 3)?;
+        assert_eq!(process_with_rules(input, &rules)?, "5");
+        Ok(())
+    }
 }
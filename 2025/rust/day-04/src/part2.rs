@@ -1,3 +1,5 @@
+use crate::neighbourhood::Rules;
+
 #[derive(Debug)]
 enum Cell {
     Paper,
@@ -43,7 +45,18 @@ impl From<char> for Cell {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let mut result = 0;
+    process_with_rules(input, &Rules::default())
+}
+
+#[tracing::instrument]
+pub fn process_with_rules(input: &str, rules: &Rules) -> miette::Result<String> {
+    Ok(removal_map(input, rules)?.removed_count().to_string())
+}
+
+/// Repeatedly removes all accessible rolls and records in which round each
+/// roll was removed
+#[tracing::instrument]
+pub fn removal_map(input: &str, rules: &Rules) -> miette::Result<RemovalMap> {
     let mut grid: Vec<Vec<Cell>> = vec![];
 
     for line in input.lines() {
@@ -62,21 +75,44 @@ pub fn process(input: &str) -> miette::Result<String> {
         "all rows are not the same length"
     );
 
+    let mut result = RemovalMap {
+        cells: grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        if cell.is_paper() {
+                            RemovalState::NeverRemoved
+                        } else {
+                            RemovalState::Empty
+                        }
+                    })
+                    .collect()
+            })
+            .collect(),
+        rounds: 0,
+    };
+
     let mut is_changed = true;
+    let mut round = 0;
     while is_changed {
-        let result_before = result;
+        is_changed = false;
+        round += 1;
         clear_out_pending(&mut grid);
         for row in 0..row_count {
             for col in 0..col_count {
-                if grid[row][col].is_paper() && neighbouring_paper_count(row, col, &grid) < 4 {
+                if grid[row][col].is_paper()
+                    && rules.is_accessible(neighbouring_paper_count(row, col, &grid, rules))
+                {
                     grid[row][col] = Cell::PendingDelete;
-                    result += 1;
+                    result.cells[row][col] = RemovalState::RemovedInRound(round);
+                    result.rounds = round;
+                    is_changed = true;
                 }
             }
         }
-        is_changed = result != result_before;
     }
-    Ok(result.to_string())
+    Ok(result)
 }
 
 fn clear_out_pending(grid: &mut [Vec<Cell>]) {
@@ -89,54 +125,88 @@ fn clear_out_pending(grid: &mut [Vec<Cell>]) {
     }
 }
 
-fn neighbouring_paper_count(row: usize, col: usize, grid: &[Vec<Cell>]) -> u8 {
-    let mut result = 0;
-    // Upper row
-    if row > 0 {
-        // Left Up
-        if col > 0 && !grid[row - 1][col - 1].is_empty() {
-            result += 1;
-        }
+fn neighbouring_paper_count(row: usize, col: usize, grid: &[Vec<Cell>], rules: &Rules) -> usize {
+    rules
+        .neighbours(row, col, grid.len(), grid[0].len())
+        .filter(|&(row, col)| !grid[row][col].is_empty())
+        .count()
+}
 
-        // Straight Up
-        if !grid[row - 1][col].is_empty() {
-            result += 1;
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalState {
+    /// There was never any paper in this cell
+    Empty,
+    /// The roll in this cell was never accessible
+    NeverRemoved,
+    /// The roll in this cell was removed in this round (first round is 1)
+    RemovedInRound(u32),
+}
 
-        // Right Up
-        if col < grid[0].len() - 1 && !grid[row - 1][col + 1].is_empty() {
-            result += 1;
-        }
+/// Records what happened to each cell of the grid while removing rolls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalMap {
+    cells: Vec<Vec<RemovalState>>,
+    rounds: u32,
+}
+
+impl RemovalMap {
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<RemovalState> {
+        self.cells.get(row)?.get(col).copied()
     }
 
-    // Left
-    if col > 0 && !grid[row][col - 1].is_empty() {
-        result += 1;
+    #[must_use]
+    pub fn rows(&self) -> &[Vec<RemovalState>] {
+        &self.cells
     }
 
-    // Right
-    if col < grid[0].len() - 1 && !grid[row][col + 1].is_empty() {
-        result += 1;
+    /// Number of rounds in which at least one roll was removed
+    #[must_use]
+    pub fn rounds(&self) -> u32 {
+        self.rounds
     }
 
-    // Lower Row
-    if row < grid.len() - 1 {
-        // Left Down
-        if col > 0 && !grid[row + 1][col - 1].is_empty() {
-            result += 1;
-        }
+    /// Number of rolls removed in total
+    #[must_use]
+    pub fn removed_count(&self) -> usize {
+        self.removed_per_round().iter().sum()
+    }
 
-        // Straight Down
-        if !grid[row + 1][col].is_empty() {
-            result += 1;
+    /// Number of rolls removed in each round (index 0 is round 1)
+    #[must_use]
+    pub fn removed_per_round(&self) -> Vec<usize> {
+        let mut result = vec![0; self.rounds as usize];
+        for state in self.cells.iter().flatten() {
+            if let RemovalState::RemovedInRound(round) = state {
+                result[*round as usize - 1] += 1;
+            }
         }
+        result
+    }
+}
 
-        // Right Down
-        if col < grid[0].len() - 1 && !grid[row + 1][col + 1].is_empty() {
-            result += 1;
+impl std::fmt::Display for RemovalMap {
+    /// Draws the grid with `.` for empty cells, `@` for rolls never removed and
+    /// the round each roll was removed in as a base 36 digit (`*` if the round
+    /// does not fit in one digit)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.cells.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for state in row {
+                let c = match state {
+                    RemovalState::Empty => '.',
+                    RemovalState::NeverRemoved => '@',
+                    RemovalState::RemovedInRound(round) => {
+                        std::char::from_digit(*round, 36).unwrap_or('*')
+                    }
+                };
+                write!(f, "{c}")?;
+            }
         }
+        Ok(())
     }
-    result
 }
 
 #[cfg(test)]
@@ -158,4 +228,22 @@ mod tests {
         assert_eq!(process(input)?, "43");
         Ok(())
     }
+
+    #[test]
+    fn test_removal_map() -> miette::Result<()> {
+        let input = "@@@@@
+@@@@@
+@@@@@";
+        let map = removal_map(input, &Rules::default())?;
+        assert_eq!(map.removed_per_round(), vec![4, 2, 4, 4, 1]);
+        assert_eq!(map.rounds(), 5);
+        assert_eq!(map.get(1, 2), Some(RemovalState::RemovedInRound(5)));
+        assert_eq!(
+            map.to_string(),
+            "13431
+24542
+13431"
+        );
+        Ok(())
+    }
 }
@@ -1,12 +1,43 @@
-use day_05::part1::process;
-use miette::Context;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+};
+
+use day_05::{part1::process, query_stream::QueryStream};
+use miette::{Context, IntoDiagnostic, bail};
+
+const USAGE: &str = "usage: part1 [--explain [PATH]]
+
+  --explain [PATH]  print fresh or spoiled for each ID (and the range that
+                    matched) reading from PATH or the bundled input";
 
 #[tracing::instrument]
 fn main() -> miette::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input1.txt");
-    let result = process(file).context("process part 1")?;
-    println!("{}", result);
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {
+            let result = process(file).context("process part 1")?;
+            println!("{}", result);
+        }
+        Some("--explain") => {
+            let reader: Box<dyn BufRead> = match args.next() {
+                Some(path) => Box::new(BufReader::new(
+                    File::open(&path)
+                        .into_diagnostic()
+                        .wrap_err_with(|| format!("failed to open {path:?}"))?,
+                )),
+                None => Box::new(file.as_bytes()),
+            };
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            for query_result in QueryStream::new(reader).context("read fresh ranges")? {
+                writeln!(out, "{}", query_result?).into_diagnostic()?;
+            }
+            out.flush().into_diagnostic()?;
+        }
+        Some(other) => bail!("unexpected argument {other:?}\n{USAGE}"),
+    }
     Ok(())
 }
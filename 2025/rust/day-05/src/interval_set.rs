@@ -0,0 +1,207 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// Set of integers stored as disjoint inclusive ranges.
+///
+/// Ranges that overlap or touch are merged when inserted, so there is only
+/// ever one range covering any run of consecutive values.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IntervalSet {
+    /// Start to end pairs (both inclusive)
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl IntervalSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds all values in `range` merging with any ranges it overlaps or
+    /// touches. Empty ranges are ignored.
+    pub fn insert(&mut self, range: RangeInclusive<u64>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        // Ranges are disjoint so their ends are sorted the same as their starts
+        let merge_list: Vec<(u64, u64)> = self
+            .ranges
+            .range(..=end.saturating_add(1))
+            .rev()
+            .take_while(|&(_, &existing_end)| existing_end.saturating_add(1) >= start)
+            .map(|(&existing_start, &existing_end)| (existing_start, existing_end))
+            .collect();
+        for (old_start, old_end) in merge_list {
+            self.ranges.remove(&old_start);
+            start = start.min(old_start);
+            end = end.max(old_end);
+        }
+        self.ranges.insert(start, end);
+    }
+
+    /// Removes all values in `range` splitting any range it partially covers.
+    /// Empty ranges are ignored.
+    pub fn remove(&mut self, range: RangeInclusive<u64>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        let overlapping: Vec<(u64, u64)> = self
+            .ranges
+            .range(..=end)
+            .rev()
+            .take_while(|&(_, &existing_end)| existing_end >= start)
+            .map(|(&existing_start, &existing_end)| (existing_start, existing_end))
+            .collect();
+        for (old_start, old_end) in overlapping {
+            self.ranges.remove(&old_start);
+            if old_start < start {
+                self.ranges.insert(old_start, start - 1);
+            }
+            if old_end > end {
+                self.ranges.insert(end + 1, old_end);
+            }
+        }
+    }
+
+    /// Returns `true` if `value` is in any of the ranges
+    #[must_use]
+    pub fn contains(&self, value: u64) -> bool {
+        self.range_containing(value).is_some()
+    }
+
+    /// Returns the (merged) range that contains `value` if there is one
+    #[must_use]
+    pub fn range_containing(&self, value: u64) -> Option<RangeInclusive<u64>> {
+        // Only the range with the closest start at or before value can contain it
+        let (&start, &end) = self.ranges.range(..=value).next_back()?;
+        (value <= end).then_some(start..=end)
+    }
+
+    /// Number of values covered by all the ranges
+    ///
+    /// NB: Returns a `u128` as the full `u64` range has one more value than fits
+    /// in a `u64`
+    #[must_use]
+    pub fn covered_len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|(&start, &end)| (end - start) as u128 + 1)
+            .sum()
+    }
+
+    /// Number of disjoint ranges in the set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Iterates over the disjoint ranges in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..=end)
+    }
+
+    /// Values that are in either set
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+        result
+    }
+
+    /// Values that are in both sets
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Self::new();
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                result.ranges.insert(start, end);
+            }
+            // Move past whichever range finishes first as it cannot overlap anything
+            // else from the other set
+            if a.end() < b.end() {
+                ours.next();
+            } else {
+                theirs.next();
+            }
+        }
+        result
+    }
+
+    /// Values that are in this set but not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.remove(range);
+        }
+        result
+    }
+}
+
+impl FromIterator<RangeInclusive<u64>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = RangeInclusive<u64>>>(iter: T) -> Self {
+        let mut result = Self::new();
+        for range in iter {
+            result.insert(range);
+        }
+        result
+    }
+}
+
+impl Extend<RangeInclusive<u64>> for IntervalSet {
+    fn extend<T: IntoIterator<Item = RangeInclusive<u64>>>(&mut self, iter: T) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(set: &IntervalSet) -> Vec<RangeInclusive<u64>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let set: IntervalSet = [3..=5, 10..=14, 16..=20, 12..=18, 6..=6]
+            .into_iter()
+            .collect();
+        assert_eq!(ranges(&set), vec![3..=6, 10..=20]);
+        assert_eq!(set.covered_len(), 15);
+        assert!(set.contains(6));
+        assert!(!set.contains(7));
+        assert_eq!(set.range_containing(15), Some(10..=20));
+        assert_eq!(set.range_containing(2), None);
+
+        let full: IntervalSet = [0..=u64::MAX].into_iter().collect();
+        assert_eq!(full.covered_len(), u64::MAX as u128 + 1);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet = [0..=10, 20..=30].into_iter().collect();
+        let b: IntervalSet = [5..=25, 30..=40].into_iter().collect();
+        assert_eq!(ranges(&a.union(&b)), vec![0..=40]);
+        assert_eq!(ranges(&a.intersection(&b)), vec![5..=10, 20..=25, 30..=30]);
+        assert_eq!(ranges(&a.difference(&b)), vec![0..=4, 26..=29]);
+        assert_eq!(ranges(&b.difference(&a)), vec![11..=19, 31..=40]);
+        assert!(a.difference(&a).is_empty());
+    }
+}
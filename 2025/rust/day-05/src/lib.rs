@@ -1,2 +1,4 @@
+pub mod interval_set;
 pub mod part1;
 pub mod part2;
+pub mod query_stream;
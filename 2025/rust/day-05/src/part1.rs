@@ -1,65 +1,62 @@
-use miette::{Context, IntoDiagnostic};
-use std::collections::BTreeMap;
+use std::str::Lines;
+
+use miette::{Context, IntoDiagnostic, bail};
+
+use crate::interval_set::IntervalSet;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
     let mut result = 0;
-    // Start end pairs
-    let mut fresh_ranges: BTreeMap<u64, u64> = BTreeMap::new();
-
     let mut lines = input.lines();
+    let fresh_ranges = parse_fresh_ranges(&mut lines)?;
 
-    // Read ranges
+    // Read queries
+    for line in lines {
+        if fresh_ranges.contains(parse_id(line)?) {
+            result += 1;
+        }
+    }
+
+    Ok(result.to_string())
+}
+
+/// Reads the ranges at the top of the input up to and including the empty
+/// line. The remaining lines are left in `lines`.
+pub fn parse_fresh_ranges(lines: &mut Lines) -> miette::Result<IntervalSet> {
+    read_fresh_ranges(lines.by_ref().map(Ok))
+}
+
+/// Same as [`parse_fresh_ranges`] but for lines that may fail to be read.
+/// Only the lines up to and including the empty line are taken from `lines`.
+pub fn read_fresh_ranges<S: AsRef<str>>(
+    mut lines: impl Iterator<Item = miette::Result<S>>,
+) -> miette::Result<IntervalSet> {
+    let mut result = IntervalSet::new();
     loop {
         let line = lines
             .next()
-            .wrap_err("should always have ranges at the top and a empty line in valid input")?;
-        if line.trim().is_empty() {
+            .wrap_err("should always have ranges at the top and a empty line in valid input")??;
+        let line = line.as_ref().trim();
+        if line.is_empty() {
             // Remaining lines are queries
             break;
         }
-        let (mut start, mut end) = parse_range(line)?;
-        let mut remove_list = vec![];
-        for (&existing_start, &existing_end) in fresh_ranges.range(..=end).rev() {
-            if start <= existing_end {
-                remove_list.push(existing_start);
-            } else {
-                break;
-            }
-        }
-        for old_start in remove_list {
-            let old_end = fresh_ranges
-                .remove(&old_start)
-                .wrap_err("we know this key must exists we just found it from the map")?;
-            start = start.min(old_start);
-            end = end.max(old_end);
-        }
-        fresh_ranges.insert(start, end);
-    }
-
-    // Read queries
-    for line in lines {
-        let query_id: u64 = line
-            .parse()
-            .into_diagnostic()
-            .wrap_err("failed to parse id")?;
-        for (&start, &end) in fresh_ranges.range(..=query_id).rev() {
-            if (start..=end).contains(&query_id) {
-                result += 1;
-                break;
-            } else if end < query_id {
-                // This is not fresh it didn't match any
-                break;
-            } else {
-                // Not a match check the next range
-            }
-        }
+        let (start, end) = parse_range(line)?;
+        result.insert(start..=end);
     }
+    Ok(result)
+}
 
-    Ok(result.to_string())
+/// Reads the ingredient ID on a query line
+pub fn parse_id(line: &str) -> miette::Result<u64> {
+    line.trim()
+        .parse()
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to parse id: {line:?}"))
 }
 
-fn parse_range(line: &str) -> miette::Result<(u64, u64)> {
+/// Reads a range like `3-5` (both ends inclusive)
+pub fn parse_range(line: &str) -> miette::Result<(u64, u64)> {
     let mut split = line.split("-");
     let start = split
         .next()
@@ -74,8 +71,12 @@ fn parse_range(line: &str) -> miette::Result<(u64, u64)> {
         .into_diagnostic()
         .wrap_err("failed to parse end of range")?;
 
-    debug_assert!(split.next().is_none(), "unexpected input found in range");
-    debug_assert!(start <= end);
+    if split.next().is_some() {
+        bail!("unexpected input found in range: {line:?}");
+    }
+    if start > end {
+        bail!("range {line:?} ends before it starts");
+    }
     Ok((start, end))
 }
 
@@ -100,4 +101,29 @@ mod tests {
         assert_eq!(process(input)?, "3");
         Ok(())
     }
+
+    #[test]
+    fn test_crlf_matches_query_stream() -> miette::Result<()> {
+        let input = "3-5 \r\n10-14\r\n\r\n4\r\n 12\r\n7\r\n";
+        assert_eq!(process(input)?, "2");
+        let fresh = crate::query_stream::QueryStream::new(input.as_bytes())?
+            .filter(|result| {
+                result
+                    .as_ref()
+                    .is_ok_and(|result| result.freshness.is_fresh())
+            })
+            .count();
+        assert_eq!(fresh, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_ranges() -> miette::Result<()> {
+        assert_eq!(parse_range("5-5")?, (5, 5));
+        let error = parse_range("10-5").expect_err("inverted");
+        assert_eq!(error.to_string(), "range \"10-5\" ends before it starts");
+        assert!(parse_range("1-2-3").is_err());
+        assert!(process("3-5\n10-5\n\n4\n").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,130 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, Lines},
+    ops::RangeInclusive,
+};
+
+use miette::{Context, IntoDiagnostic};
+
+use crate::{
+    interval_set::IntervalSet,
+    part1::{parse_id, read_fresh_ranges},
+};
+
+/// Outcome of looking up a single ingredient ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryResult {
+    pub id: u64,
+    pub freshness: Freshness,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Freshness {
+    /// The ID is fresh and is inside this (merged) range
+    Fresh(RangeInclusive<u64>),
+    Spoiled,
+}
+
+impl Freshness {
+    /// Returns `true` if the freshness is [`Fresh`].
+    ///
+    /// [`Fresh`]: Freshness::Fresh
+    #[must_use]
+    pub fn is_fresh(&self) -> bool {
+        matches!(self, Self::Fresh(..))
+    }
+}
+
+impl Display for QueryResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.freshness {
+            Freshness::Fresh(range) => {
+                write!(f, "{}: fresh ({}-{})", self.id, range.start(), range.end())
+            }
+            Freshness::Spoiled => write!(f, "{}: spoiled", self.id),
+        }
+    }
+}
+
+/// Reads the ranges from the top of `reader` then lazily reads and answers
+/// the queries after the empty line as it is iterated
+pub struct QueryStream<R> {
+    fresh_ranges: IntervalSet,
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> QueryStream<R> {
+    pub fn new(reader: R) -> miette::Result<Self> {
+        let mut lines = reader.lines();
+        let fresh_ranges = read_fresh_ranges(
+            lines
+                .by_ref()
+                .map(|line| line.into_diagnostic().wrap_err("failed to read range")),
+        )?;
+        Ok(Self {
+            fresh_ranges,
+            lines,
+        })
+    }
+
+    #[must_use]
+    pub fn fresh_ranges(&self) -> &IntervalSet {
+        &self.fresh_ranges
+    }
+}
+
+impl<R: BufRead> Iterator for QueryStream<R> {
+    type Item = miette::Result<QueryResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.lines.next()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e).into_diagnostic().wrap_err("failed to read query")),
+        };
+        Some(parse_id(&line).map(|id| {
+            QueryResult {
+                id,
+                freshness: self
+                    .fresh_ranges
+                    .range_containing(id)
+                    .map_or(Freshness::Spoiled, Freshness::Fresh),
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_stream() -> miette::Result<()> {
+        let input = "3-5
+10-14
+16-20
+12-18
+
+1
+5
+8
+11
+17
+32
+";
+        let results = QueryStream::new(input.as_bytes())?
+            .map(|x| x.map(|result| result.to_string()))
+            .collect::<miette::Result<Vec<_>>>()?;
+        assert_eq!(
+            results,
+            vec![
+                "1: spoiled",
+                "5: fresh (3-5)",
+                "8: spoiled",
+                "11: fresh (10-20)",
+                "17: fresh (10-20)",
+                "32: spoiled",
+            ]
+        );
+        Ok(())
+    }
+}
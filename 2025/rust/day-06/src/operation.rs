@@ -0,0 +1,139 @@
+use std::fmt::Display;
+
+use miette::{Context, bail};
+
+/// Operations that can be written under a problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Min,
+    Max,
+    /// Joins the digits of the numbers together (`12 || 34` is `1234`)
+    Concatenate,
+}
+
+/// Every symbol that is recognized and the operation it maps to
+pub const OPERATIONS: &[(&str, Operation)] = &[
+    ("+", Operation::Add),
+    ("-", Operation::Subtract),
+    ("*", Operation::Multiply),
+    ("/", Operation::Divide),
+    ("<", Operation::Min),
+    ("min", Operation::Min),
+    (">", Operation::Max),
+    ("max", Operation::Max),
+    ("|", Operation::Concatenate),
+    ("||", Operation::Concatenate),
+];
+
+impl Operation {
+    /// Combines `values` from left to right (`a - b - c` for [`Subtract`]).
+    ///
+    /// Fails instead of overflowing, on division by zero, if the result would
+    /// be negative or if there are no values for an operation that needs at
+    /// least one.
+    ///
+    /// [`Subtract`]: Operation::Subtract
+    pub fn evaluate(&self, values: &[u64]) -> miette::Result<u64> {
+        let mut values = values.iter().copied();
+        let mut result = match (self.identity(), values.next()) {
+            (Some(identity), None) => return Ok(identity),
+            (_, Some(first)) => first,
+            (None, None) => bail!("{self} needs at least one value"),
+        };
+        for value in values {
+            result = self
+                .apply(result, value)
+                .wrap_err_with(|| format!("failed to evaluate {result} {self} {value}"))?;
+        }
+        Ok(result)
+    }
+
+    fn apply(&self, x: u64, y: u64) -> miette::Result<u64> {
+        Ok(match self {
+            Operation::Add => x.checked_add(y).wrap_err("overflow")?,
+            Operation::Subtract => x.checked_sub(y).wrap_err("result would be negative")?,
+            Operation::Multiply => x.checked_mul(y).wrap_err("overflow")?,
+            Operation::Divide => x.checked_div(y).wrap_err("division by zero")?,
+            Operation::Min => x.min(y),
+            Operation::Max => x.max(y),
+            Operation::Concatenate => {
+                let shift = 10u64
+                    .checked_pow(y.checked_ilog10().unwrap_or_default() + 1)
+                    .wrap_err("overflow")?;
+                x.checked_mul(shift)
+                    .and_then(|x| x.checked_add(y))
+                    .wrap_err("overflow")?
+            }
+        })
+    }
+
+    /// The value of the operation with no inputs if it has one
+    fn identity(&self) -> Option<u64> {
+        match self {
+            Operation::Add => Some(0),
+            Operation::Multiply => Some(1),
+            Operation::Subtract
+            | Operation::Divide
+            | Operation::Min
+            | Operation::Max
+            | Operation::Concatenate => None,
+        }
+    }
+
+    /// The main symbol used for this operation
+    #[must_use]
+    pub fn symbol(&self) -> &'static str {
+        OPERATIONS
+            .iter()
+            .find(|(_, operation)| operation == self)
+            .map(|(symbol, _)| *symbol)
+            .expect("all operations are in the list")
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl TryFrom<&str> for Operation {
+    type Error = miette::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match OPERATIONS.iter().find(|(symbol, _)| *symbol == value) {
+            Some((_, operation)) => Ok(*operation),
+            None => bail!("unexpected operation found: {value:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() -> miette::Result<()> {
+        assert_eq!(Operation::try_from("-")?.evaluate(&[10, 3, 2])?, 5);
+        assert_eq!(Operation::try_from("/")?.evaluate(&[100, 5, 2])?, 10);
+        assert_eq!(Operation::try_from("min")?.evaluate(&[7, 3, 9])?, 3);
+        assert_eq!(Operation::try_from(">")?.evaluate(&[7, 3, 9])?, 9);
+        assert_eq!(Operation::try_from("||")?.evaluate(&[12, 0, 345])?, 120345);
+        assert_eq!(Operation::try_from("*")?.evaluate(&[])?, 1);
+        assert!(Operation::try_from("?").is_err());
+        assert!(Operation::Min.evaluate(&[]).is_err());
+        assert!(Operation::Subtract.evaluate(&[1, 2]).is_err());
+        assert!(Operation::Divide.evaluate(&[1, 0]).is_err());
+        assert!(Operation::Multiply.evaluate(&[u64::MAX, 2]).is_err());
+        assert!(
+            Operation::Concatenate
+                .evaluate(&[u64::MAX / 10, 10])
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use miette::{Context, IntoDiagnostic, bail};
+
+use crate::operation::Operation;
+
+/// The math worksheet as a grid of characters.
+///
+/// Rows are padded with spaces to the same width (trailing spaces are often
+/// trimmed by editors) and `\r` from Windows line endings is removed. Problems
+/// are separated by columns that are blank in every row.
+#[derive(Debug)]
+pub struct Worksheet {
+    /// Rows that contain the numbers
+    number_rows: Vec<String>,
+    /// Last row which contains the operations
+    operator_row: String,
+}
+
+/// How the numbers of a problem are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reading {
+    /// See [`Problem::row_numbers`]
+    Rows,
+    /// See [`Problem::column_numbers`]
+    Columns,
+}
+
+/// A single problem on the worksheet (the columns between two blank columns)
+#[derive(Debug)]
+pub struct Problem<'a> {
+    /// Index of the first column of the problem on the worksheet
+    start_col: usize,
+    /// Part of each number row that belongs to this problem
+    rows: Vec<&'a str>,
+    /// The operator written under the problem (with spaces trimmed)
+    operator: &'a str,
+}
+
+impl Worksheet {
+    pub fn parse(input: &str) -> miette::Result<Self> {
+        let mut lines: Vec<&str> = input
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        if let Some((row, _)) = lines.iter().enumerate().find(|(_, line)| !line.is_ascii()) {
+            bail!("only ascii is expected but found other characters on row {row}");
+        }
+
+        let width = lines
+            .iter()
+            .map(|line| line.len())
+            .max()
+            .unwrap_or_default();
+        let mut rows: Vec<String> = lines
+            .into_iter()
+            .map(|line| format!("{line:<width$}"))
+            .collect();
+        let operator_row = rows
+            .pop()
+            .wrap_err("last row must contain the operations")?;
+        if rows.is_empty() {
+            bail!("expected at least one row of numbers above the operations");
+        }
+
+        Ok(Self {
+            number_rows: rows,
+            operator_row,
+        })
+    }
+
+    /// Width of the worksheet after padding
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.operator_row.len()
+    }
+
+    /// Problems in order from left to right
+    #[must_use]
+    pub fn problems(&self) -> Vec<Problem<'_>> {
+        let mut result = vec![];
+        let mut start_col = None;
+        for col in 0..=self.width() {
+            let is_blank = col == self.width()
+                || self
+                    .number_rows
+                    .iter()
+                    .chain(std::iter::once(&self.operator_row))
+                    .all(|row| row.as_bytes()[col] == b' ');
+            match (is_blank, start_col) {
+                (false, None) => start_col = Some(col),
+                (true, Some(start)) => {
+                    result.push(Problem {
+                        start_col: start,
+                        rows: self
+                            .number_rows
+                            .iter()
+                            .map(|row| &row[start..col])
+                            .collect(),
+                        operator: self.operator_row[start..col].trim(),
+                    });
+                    start_col = None;
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+impl<'a> Problem<'a> {
+    /// Index of the first column of the problem on the worksheet
+    #[must_use]
+    pub fn start_col(&self) -> usize {
+        self.start_col
+    }
+
+    #[must_use]
+    pub fn operator(&self) -> &'a str {
+        self.operator
+    }
+
+    /// Reads the numbers and applies the operator to them
+    pub fn solve(&self, reading: Reading) -> miette::Result<u64> {
+        let context = || {
+            format!(
+                "failed to solve problem starting at column {}",
+                self.start_col
+            )
+        };
+        let operation = Operation::try_from(self.operator).wrap_err_with(context)?;
+        let values = match reading {
+            Reading::Rows => self.row_numbers(),
+            Reading::Columns => self.column_numbers(),
+        }
+        .wrap_err_with(context)?;
+        operation.evaluate(&values).wrap_err_with(context)
+    }
+
+    /// Numbers read normally (one per row from top to bottom). Rows that are
+    /// blank for this problem are skipped.
+    pub fn row_numbers(&self) -> miette::Result<Vec<u64>> {
+        self.rows
+            .iter()
+            .map(|row| row.trim())
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse()
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("failed to convert to number: {value:?}"))
+            })
+            .collect()
+    }
+
+    /// Numbers read in columns (most significant digit at the top) going from
+    /// the rightmost column to the leftmost
+    pub fn column_numbers(&self) -> miette::Result<Vec<u64>> {
+        let width = self.rows.first().map_or(0, |row| row.len());
+        let mut result = Vec::with_capacity(width);
+        for col in (0..width).rev() {
+            let digits: String = self
+                .rows
+                .iter()
+                .map(|row| row.as_bytes()[col] as char)
+                .collect();
+            let digits = digits.trim();
+            if digits.is_empty() {
+                // Numbers do not have to use every column of a problem
+                continue;
+            }
+            if digits.contains(' ') {
+                bail!(
+                    "digits in column {} are not contiguous: {digits:?}",
+                    self.start_col + col
+                );
+            }
+            result.push(
+                digits
+                    .parse()
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("failed to convert to number: {digits:?}"))?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ragged_and_crlf() -> miette::Result<()> {
+        // Same as the example with trailing spaces trimmed and Windows line endings
+        let input = "123 328  51 64\r\n 45 64  387 23\r\n  6 98  215 314\r\n*   +   *   +\r\n";
+        let worksheet = Worksheet::parse(input)?;
+        let problems = worksheet.problems();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(problems[3].start_col(), 12);
+        assert_eq!(problems[3].operator(), "+");
+        assert_eq!(problems[3].row_numbers()?, vec![64, 23, 314]);
+        assert_eq!(problems[3].column_numbers()?, vec![4, 431, 623]);
+        assert_eq!(crate::part1::process(input)?, "4277556");
+        assert_eq!(crate::part2::process(input)?, "3263827");
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow_names_column() -> miette::Result<()> {
+        let input = "1 99999999999
+2 99999999999
++ *";
+        let worksheet = Worksheet::parse(input)?;
+        let problems = worksheet.problems();
+        assert_eq!(problems[0].solve(Reading::Rows)?, 3);
+        let err = problems[1].solve(Reading::Rows).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to solve problem starting at column 2"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_contiguous_digits() -> miette::Result<()> {
+        let input = "1
+
+2
++ ";
+        let worksheet = Worksheet::parse(input)?;
+        assert!(worksheet.problems()[0].column_numbers().is_err());
+        Ok(())
+    }
+}
@@ -1,2 +1,4 @@
+pub mod manifold;
 pub mod part1;
 pub mod part2;
+pub mod timeline_count;
@@ -0,0 +1,549 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use miette::bail;
+
+use crate::timeline_count::TimelineCount;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        }
+    }
+
+    /// The two sides of a beam travelling in this direction. West then east
+    /// for vertical beams and north then south for horizontal beams.
+    fn sides(&self) -> [Direction; 2] {
+        match self {
+            Direction::Up | Direction::Down => [Direction::Left, Direction::Right],
+            Direction::Left | Direction::Right => [Direction::Up, Direction::Down],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    /// Emits a beam travelling down
+    Source,
+    /// `/`
+    MirrorForward,
+    /// `\`
+    MirrorBackward,
+    /// Splits a beam into two beams that continue in the same direction from
+    /// either side of the splitter. One sided splitters only emit the first
+    /// (west / north) or only the second (east / south) of those beams.
+    Splitter {
+        first: bool,
+        second: bool,
+    },
+}
+
+impl Cell {
+    /// Direction a beam leaves a mirror in when it arrives travelling in
+    /// `direction`
+    fn reflect(&self, direction: Direction) -> Direction {
+        use Direction::*;
+        match (self, direction) {
+            (Cell::MirrorForward, Right) | (Cell::MirrorBackward, Left) => Up,
+            (Cell::MirrorForward, Left) | (Cell::MirrorBackward, Right) => Down,
+            (Cell::MirrorForward, Up) | (Cell::MirrorBackward, Down) => Right,
+            (Cell::MirrorForward, Down) | (Cell::MirrorBackward, Up) => Left,
+            _ => direction,
+        }
+    }
+}
+
+impl TryFrom<char> for Cell {
+    type Error = miette::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            '.' => Self::Empty,
+            'S' => Self::Source,
+            '/' => Self::MirrorForward,
+            '\\' => Self::MirrorBackward,
+            '^' => Self::Splitter {
+                first: true,
+                second: true,
+            },
+            '[' => Self::Splitter {
+                first: true,
+                second: false,
+            },
+            ']' => Self::Splitter {
+                first: false,
+                second: true,
+            },
+            other => bail!("unexpected cell value found: {other:?}"),
+        })
+    }
+}
+
+/// The tachyon manifold.
+///
+/// | Cell | Meaning                                                           |
+/// |------|-------------------------------------------------------------------|
+/// | `.`  | Empty                                                             |
+/// | `S`  | Source of a beam travelling down                                  |
+/// | `/`  | Mirror                                                            |
+/// | `\`  | Mirror                                                            |
+/// | `^`  | Splitter that emits beams on both sides                           |
+/// | `[`  | Splitter that only emits on the west (north for horizontal beams) |
+/// | `]`  | Splitter that only emits on the east (south for horizontal beams) |
+///
+/// Beams leaving a splitter continue in the direction they were travelling
+/// from the cells on either side of it (they do not interact with those side
+/// cells). With only sources and `^` splitters this is the puzzle's manifold.
+///
+/// Rows may have different lengths (missing cells are treated as empty) and
+/// there may be any number of sources on any row.
+#[derive(Debug)]
+pub struct Manifold {
+    rows: Vec<Vec<Cell>>,
+    width: usize,
+}
+
+/// A beam entering a cell travelling in a direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeamPosition {
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+}
+
+impl Display for BeamPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}) {:?}", self.row, self.col, self.direction)
+    }
+}
+
+/// Where a beam goes next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Enter(BeamPosition),
+    /// The beam left the manifold travelling in this direction
+    Exit(Direction),
+}
+
+/// Number of timelines that left the manifold through each side
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExitCounts {
+    pub top: TimelineCount,
+    pub bottom: TimelineCount,
+    pub left: TimelineCount,
+    pub right: TimelineCount,
+}
+
+impl ExitCounts {
+    fn side_mut(&mut self, direction: Direction) -> &mut TimelineCount {
+        match direction {
+            Direction::Up => &mut self.top,
+            Direction::Down => &mut self.bottom,
+            Direction::Left => &mut self.left,
+            Direction::Right => &mut self.right,
+        }
+    }
+
+    /// Total number of timelines (however they ended)
+    #[must_use]
+    pub fn total(&self) -> TimelineCount {
+        let mut result = self.top.clone();
+        result += &self.bottom;
+        result += &self.left;
+        result += &self.right;
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timelines {
+    /// Every timeline leaves the manifold
+    Finite(ExitCounts),
+    /// Beams can go around this loop forever so there are infinitely many
+    /// timelines
+    Endless { cycle: Vec<BeamPosition> },
+}
+
+/// Outcome of sending beams through the manifold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeamReport {
+    /// Number of distinct splitters that were hit by at least one beam
+    pub splitters_hit: usize,
+    /// Number of sources found
+    pub sources: usize,
+    pub timelines: Timelines,
+}
+
+impl Manifold {
+    pub fn parse(input: &str) -> miette::Result<Self> {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(row, line)| {
+                line.trim_end_matches('\r')
+                    .chars()
+                    .map(Cell::try_from)
+                    .collect::<miette::Result<Vec<_>>>()
+                    .map_err(|e| e.wrap_err(format!("invalid cell on row {row}")))
+            })
+            .collect::<miette::Result<Vec<_>>>()?;
+        if rows.is_empty() {
+            bail!("first row must be present");
+        }
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or_default();
+        Ok(Self { rows, width })
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        self.rows[row].get(col).copied().unwrap_or(Cell::Empty)
+    }
+
+    fn state_index(&self, position: &BeamPosition) -> usize {
+        (position.row * self.width + position.col) * 4 + position.direction.index()
+    }
+
+    fn state_position(&self, index: usize) -> BeamPosition {
+        let cell = index / 4;
+        BeamPosition {
+            row: cell / self.width,
+            col: cell % self.width,
+            direction: Direction::ALL
+                .into_iter()
+                .find(|d| d.index() == index % 4)
+                .expect("index is always less than 4"),
+        }
+    }
+
+    /// Moves one cell from (`row`, `col`) in `direction`
+    fn step(&self, row: usize, col: usize, direction: Direction) -> Step {
+        let next = match direction {
+            Direction::Up => row.checked_sub(1).map(|row| (row, col)),
+            Direction::Down => (row + 1 < self.rows.len()).then_some((row + 1, col)),
+            Direction::Left => col.checked_sub(1).map(|col| (row, col)),
+            Direction::Right => (col + 1 < self.width).then_some((row, col + 1)),
+        };
+        match next {
+            Some((row, col)) => Step::Enter(BeamPosition {
+                row,
+                col,
+                direction,
+            }),
+            None => Step::Exit(direction),
+        }
+    }
+
+    /// Where a beam goes after entering a cell
+    fn next_steps(&self, position: &BeamPosition) -> Vec<Step> {
+        let BeamPosition {
+            row,
+            col,
+            direction,
+        } = *position;
+        let cell = self.cell(row, col);
+        match cell {
+            Cell::Empty | Cell::Source => vec![self.step(row, col, direction)],
+            Cell::MirrorForward | Cell::MirrorBackward => {
+                vec![self.step(row, col, cell.reflect(direction))]
+            }
+            Cell::Splitter { first, second } => direction
+                .sides()
+                .into_iter()
+                .zip([first, second])
+                .filter(|(_, is_emitted)| *is_emitted)
+                .map(|(side, _)| match self.step(row, col, side) {
+                    Step::Enter(side_position) => {
+                        self.step(side_position.row, side_position.col, direction)
+                    }
+                    exit @ Step::Exit(_) => exit,
+                })
+                .collect(),
+        }
+    }
+
+    /// Sends beams from every source through the manifold counting the
+    /// splitters hit and the number of timelines.
+    #[must_use]
+    pub fn simulate(&self) -> BeamReport {
+        let state_count = self.rows.len() * self.width * 4;
+        let mut successors: Vec<Option<Vec<Step>>> = vec![None; state_count];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; state_count];
+        let mut splitter_hit = vec![false; self.rows.len() * self.width];
+
+        // Beams start in the cell below each source
+        let mut starts = vec![];
+        for (row, cells) in self.rows.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if *cell == Cell::Source {
+                    starts.push(self.step(row, col, Direction::Down));
+                }
+            }
+        }
+
+        // Find every state a beam can reach
+        let mut stack: Vec<BeamPosition> = starts
+            .iter()
+            .filter_map(|step| match step {
+                Step::Enter(position) => Some(*position),
+                Step::Exit(_) => None,
+            })
+            .collect();
+        while let Some(position) = stack.pop() {
+            let index = self.state_index(&position);
+            if successors[index].is_some() {
+                continue;
+            }
+            if matches!(self.cell(position.row, position.col), Cell::Splitter { .. }) {
+                splitter_hit[position.row * self.width + position.col] = true;
+            }
+            let next_steps = self.next_steps(&position);
+            for step in next_steps.iter() {
+                if let Step::Enter(next) = step {
+                    predecessors[self.state_index(next)].push(index);
+                    stack.push(*next);
+                }
+            }
+            successors[index] = Some(next_steps);
+        }
+
+        BeamReport {
+            splitters_hit: splitter_hit.iter().filter(|&&x| x).count(),
+            sources: starts.len(),
+            timelines: self.count_timelines(&starts, &successors, &predecessors),
+        }
+    }
+
+    /// Counts the timelines by pushing counts through the reachable states in
+    /// topological order (Kahn's algorithm). Any states that cannot be ordered
+    /// are part of or after a cycle.
+    fn count_timelines(
+        &self,
+        starts: &[Step],
+        successors: &[Option<Vec<Step>>],
+        predecessors: &[Vec<usize>],
+    ) -> Timelines {
+        let mut exits = ExitCounts::default();
+        let mut counts = vec![TimelineCount::default(); successors.len()];
+        let one = TimelineCount::from(1);
+        let mut in_degree: Vec<usize> = predecessors.iter().map(|x| x.len()).collect();
+        for step in starts {
+            match step {
+                Step::Enter(position) => counts[self.state_index(position)] += &one,
+                Step::Exit(direction) => *exits.side_mut(*direction) += &one,
+            }
+        }
+
+        let mut queue: VecDeque<usize> = (0..successors.len())
+            .filter(|&i| successors[i].is_some() && in_degree[i] == 0)
+            .collect();
+        let mut processed = 0;
+        while let Some(index) = queue.pop_front() {
+            processed += 1;
+            let count = std::mem::take(&mut counts[index]);
+            for step in successors[index].iter().flatten() {
+                match step {
+                    Step::Enter(next) => {
+                        let next = self.state_index(next);
+                        counts[next] += &count;
+                        in_degree[next] -= 1;
+                        if in_degree[next] == 0 {
+                            queue.push_back(next);
+                        }
+                    }
+                    Step::Exit(direction) => *exits.side_mut(*direction) += &count,
+                }
+            }
+        }
+
+        let reachable = successors.iter().filter(|x| x.is_some()).count();
+        if processed == reachable {
+            return Timelines::Finite(exits);
+        }
+
+        // Every unprocessed state has an unprocessed predecessor so walking
+        // backwards must eventually revisit a state
+        let mut current = (0..successors.len())
+            .find(|&i| in_degree[i] > 0)
+            .expect("some states were not processed");
+        let mut visited_order = vec![];
+        let mut visited_at = std::collections::HashMap::new();
+        while !visited_at.contains_key(&current) {
+            visited_at.insert(current, visited_order.len());
+            visited_order.push(current);
+            current = *predecessors[current]
+                .iter()
+                .find(|&&i| in_degree[i] > 0)
+                .expect("unprocessed state must have an unprocessed predecessor");
+        }
+        let mut cycle: Vec<BeamPosition> = visited_order[visited_at[&current]..]
+            .iter()
+            .map(|&i| self.state_position(i))
+            .collect();
+        cycle.reverse();
+        Timelines::Endless { cycle }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_splitters() -> miette::Result<()> {
+        let input = "S..S
+....
+^..^
+";
+        let report = Manifold::parse(input)?.simulate();
+        assert_eq!(report.splitters_hit, 2);
+        assert_eq!(report.sources, 2);
+        assert_eq!(
+            report.timelines,
+            Timelines::Finite(ExitCounts {
+                bottom: 2.into(),
+                left: 1.into(),
+                right: 1.into(),
+                ..Default::default()
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ragged_rows_and_later_sources() -> miette::Result<()> {
+        let input = ".S
+.
+.^.S
+...^
+";
+        let report = Manifold::parse(input)?.simulate();
+        // The short row does not stop the first beam and the beam from the
+        // second source is split out of the right side
+        assert_eq!(report.sources, 2);
+        assert_eq!(report.splitters_hit, 2);
+        let Timelines::Finite(exits) = report.timelines else {
+            panic!("no cycles in input");
+        };
+        assert_eq!(exits.right, 1.into());
+        assert_eq!(exits.bottom, 3.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirrors_and_one_sided_splitters() -> miette::Result<()> {
+        // Beam is turned right by the mirror below the source, split (north side
+        // only) by `[` and then turned down by the mirror on the row above
+        let input = ".S....
+.....\\
+.\\.[..
+......
+";
+        let report = Manifold::parse(input)?.simulate();
+        assert_eq!(report.splitters_hit, 1);
+        assert_eq!(
+            report.timelines,
+            Timelines::Finite(ExitCounts {
+                bottom: 1.into(),
+                ..Default::default()
+            })
+        );
+
+        // `]` sends the beam along the south side instead so it misses the mirror
+        let report = Manifold::parse(&input.replace('[', "]"))?.simulate();
+        assert_eq!(
+            report.timelines,
+            Timelines::Finite(ExitCounts {
+                right: 1.into(),
+                ..Default::default()
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_detection() -> miette::Result<()> {
+        // Both beams from the splitter end up going around the ring of mirrors
+        let input = "..S.
+./.\\
+..^.
+.\\./
+";
+        let report = Manifold::parse(input)?.simulate();
+        assert_eq!(report.splitters_hit, 1);
+        let Timelines::Endless { cycle } = report.timelines else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(cycle.len(), 8);
+        assert!(
+            cycle
+                .iter()
+                .all(|p| [1, 3].contains(&p.row) || [1, 3].contains(&p.col))
+        );
+        Ok(())
+    }
+
+    /// Every beam is split on every row so there are `2^splitter_rows`
+    /// timelines
+    fn doubling_manifold(splitter_rows: usize) -> String {
+        let width = 2 * splitter_rows + 3;
+        let source_col = splitter_rows + 1;
+        let mut rows = vec![
+            (0..width)
+                .map(|col| if col == source_col { 'S' } else { '.' })
+                .collect::<String>(),
+        ];
+        for row in 0..splitter_rows {
+            // Beams move to the columns with the other parity on each row
+            rows.push(
+                (0..width)
+                    .map(|col| {
+                        if (col + row) % 2 == source_col % 2 {
+                            '^'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        rows.join("\n")
+    }
+
+    #[test]
+    fn test_timelines_do_not_overflow() -> miette::Result<()> {
+        let report = Manifold::parse(&doubling_manifold(100))?.simulate();
+        let Timelines::Finite(exits) = report.timelines else {
+            panic!("no cycles in input");
+        };
+        assert_eq!(exits.total(), TimelineCount::U128(1 << 100));
+
+        let report = Manifold::parse(&doubling_manifold(130))?.simulate();
+        let Timelines::Finite(exits) = report.timelines else {
+            panic!("no cycles in input");
+        };
+        assert_eq!(exits.total().representation(), "arbitrary precision");
+        assert_eq!(
+            exits.total(),
+            TimelineCount::Big(num_bigint::BigUint::from(1u8) << 130)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fmt::Display, ops::AddAssign};
+
+use num_bigint::BigUint;
+
+/// Number of timelines.
+///
+/// Timelines double at every splitter so counts are kept in a `u128` with
+/// checked additions and switch to an arbitrary precision integer the first
+/// time a sum does not fit. The variant shows which representation was needed
+/// but counts compare by value whichever variant holds them.
+#[derive(Debug, Clone)]
+pub enum TimelineCount {
+    U128(u128),
+    Big(BigUint),
+}
+
+impl TimelineCount {
+    /// Short name of the representation in use
+    #[must_use]
+    pub fn representation(&self) -> &'static str {
+        match self {
+            TimelineCount::U128(_) => "u128",
+            TimelineCount::Big(_) => "arbitrary precision",
+        }
+    }
+
+    /// Returns `true` if the count did not fit in a `u128`
+    #[must_use]
+    pub fn is_big(&self) -> bool {
+        matches!(self, Self::Big(..))
+    }
+
+    fn to_big(&self) -> BigUint {
+        match self {
+            TimelineCount::U128(value) => BigUint::from(*value),
+            TimelineCount::Big(value) => value.clone(),
+        }
+    }
+}
+
+impl PartialEq for TimelineCount {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TimelineCount::U128(lhs), TimelineCount::U128(rhs)) => lhs == rhs,
+            _ => self.to_big() == other.to_big(),
+        }
+    }
+}
+
+impl Eq for TimelineCount {}
+
+impl Default for TimelineCount {
+    fn default() -> Self {
+        Self::U128(0)
+    }
+}
+
+impl From<u128> for TimelineCount {
+    fn from(value: u128) -> Self {
+        Self::U128(value)
+    }
+}
+
+impl AddAssign<&TimelineCount> for TimelineCount {
+    fn add_assign(&mut self, rhs: &TimelineCount) {
+        match (&mut *self, rhs) {
+            (TimelineCount::U128(lhs), TimelineCount::U128(rhs)) => match lhs.checked_add(*rhs) {
+                Some(sum) => *lhs = sum,
+                None => *self = TimelineCount::Big(BigUint::from(*lhs) + *rhs),
+            },
+            (TimelineCount::Big(lhs), TimelineCount::U128(rhs)) => *lhs += *rhs,
+            (TimelineCount::Big(lhs), TimelineCount::Big(rhs)) => *lhs += rhs,
+            (TimelineCount::U128(_), TimelineCount::Big(rhs)) => {
+                *self = TimelineCount::Big(self.to_big() + rhs)
+            }
+        }
+    }
+}
+
+impl Display for TimelineCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineCount::U128(value) => write!(f, "{value}"),
+            TimelineCount::Big(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promotes_on_overflow() {
+        let mut count = TimelineCount::from(u128::MAX - 1);
+        count += &TimelineCount::from(1);
+        assert_eq!(count, TimelineCount::U128(u128::MAX));
+        count += &TimelineCount::from(1);
+        assert!(count.is_big());
+        assert_eq!(count.to_string(), "340282366920938463463374607431768211456");
+    }
+
+    #[test]
+    fn test_equal_by_value() {
+        let small = TimelineCount::from(5);
+        assert_eq!(small, TimelineCount::Big(BigUint::from(5u32)));
+        assert_eq!(TimelineCount::Big(BigUint::from(5u32)), small);
+        assert_ne!(small, TimelineCount::Big(BigUint::from(6u32)));
+    }
+}
@@ -1,12 +1,66 @@
-use day_08::part2::process;
-use miette::Context;
+use day_08::{
+    metric::Metric, part1::parse_junction_boxes, part2::process_with_metric,
+    spanning_forest::SpanningForest,
+};
+use miette::{Context, IntoDiagnostic, bail};
+
+const USAGE: &str = "usage: part2 [--metric NAME] [--json|--dot [CONNECTIONS]]
+
+  --metric NAME         measure distances with euclidean (default), manhattan
+                        or chebyshev
+  --json [CONNECTIONS]  print the spanning forest as JSON with the circuits
+                        after the first CONNECTIONS pairs (default all)
+  --dot [CONNECTIONS]   same as --json but as Graphviz DOT";
 
 #[tracing::instrument]
 fn main() -> miette::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input2.txt");
-    let result = process(file).context("process part 2")?;
-    println!("{}", result);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let metric = match args.iter().position(|arg| arg == "--metric") {
+        Some(index) => {
+            let Some(name) = args.get(index + 1) else {
+                bail!("missing name after --metric\n{USAGE}");
+            };
+            let metric = Metric::try_from(name.as_str()).wrap_err(USAGE)?;
+            args.drain(index..index + 2);
+            metric
+        }
+        None => Metric::default(),
+    };
+    let mut args = args.into_iter();
+    let format = args.next();
+    let connections = args
+        .next()
+        .map(|value| {
+            value
+                .parse::<usize>()
+                .into_diagnostic()
+                .wrap_err_with(|| format!("invalid number of connections {value:?}\n{USAGE}"))
+        })
+        .transpose()?;
+    if let Some(extra) = args.next() {
+        bail!("unexpected argument {extra:?}\n{USAGE}");
+    }
+    match format.as_deref() {
+        None => {
+            let result = process_with_metric(file, metric).context("process part 2")?;
+            println!("{}", result);
+        }
+        Some("--json") => {
+            print!(
+                "{}",
+                SpanningForest::new(parse_junction_boxes(file)?, metric).to_json(connections)
+            );
+        }
+        Some("--dot") => {
+            print!(
+                "{}",
+                SpanningForest::new(parse_junction_boxes(file)?, metric).to_dot(connections)
+            );
+        }
+        Some(other) => bail!("unexpected argument {other:?}\n{USAGE}"),
+    }
     Ok(())
 }
@@ -1,2 +1,5 @@
+pub mod metric;
+pub mod nearest_pairs;
 pub mod part1;
 pub mod part2;
+pub mod spanning_forest;
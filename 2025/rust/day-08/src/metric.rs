@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+use miette::bail;
+
+/// How the distance between two junction boxes is measured
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Straight line distance (used by the puzzle)
+    #[default]
+    Euclidean,
+    /// Sum of the distances along each axis
+    Manhattan,
+    /// Largest of the distances along each axis
+    Chebyshev,
+}
+
+/// Every name that is recognized and the metric it maps to
+pub const METRICS: &[(&str, Metric)] = &[
+    ("euclidean", Metric::Euclidean),
+    ("manhattan", Metric::Manhattan),
+    ("chebyshev", Metric::Chebyshev),
+];
+
+impl Metric {
+    /// Exact value that orders pairs the same way as the actual distance.
+    ///
+    /// For [`Euclidean`] this is the squared distance (to stay in integers),
+    /// for the others it is the distance itself. Never decreases as the
+    /// distance along any axis grows, which the k-d tree relies on for its
+    /// lower bounds. Coordinates must be within +/- [`MAX_COORDINATE`] so the
+    /// squared distance cannot overflow.
+    ///
+    /// [`Euclidean`]: Metric::Euclidean
+    /// [`MAX_COORDINATE`]: crate::part1::MAX_COORDINATE
+    #[must_use]
+    pub fn distance(&self, a: [i64; 3], b: [i64; 3]) -> i128 {
+        let deltas = a
+            .into_iter()
+            .zip(b)
+            .map(|(a, b)| (a as i128 - b as i128).abs());
+        match self {
+            Metric::Euclidean => deltas.map(|delta| delta * delta).sum(),
+            Metric::Manhattan => deltas.sum(),
+            Metric::Chebyshev => deltas.max().unwrap_or_default(),
+        }
+    }
+
+    /// Converts a value from [`Self::distance`] into the actual distance
+    #[must_use]
+    pub fn length(&self, distance: i128) -> f64 {
+        match self {
+            Metric::Euclidean => (distance as f64).sqrt(),
+            Metric::Manhattan | Metric::Chebyshev => distance as f64,
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        METRICS
+            .iter()
+            .find(|(_, metric)| metric == self)
+            .map(|(name, _)| *name)
+            .expect("all metrics are in the list")
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl TryFrom<&str> for Metric {
+    type Error = miette::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match METRICS.iter().find(|(name, _)| *name == value) {
+            Some((_, metric)) => Ok(*metric),
+            None => bail!("unexpected metric: {value:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let a = [1, -2, 3];
+        let b = [4, 2, 3];
+        assert_eq!(Metric::Euclidean.distance(a, b), 25);
+        assert_eq!(Metric::Euclidean.length(25), 5.0);
+        assert_eq!(Metric::Manhattan.distance(a, b), 7);
+        assert_eq!(Metric::Chebyshev.distance(a, b), 4);
+        assert_eq!(
+            Metric::Manhattan.distance([i64::MIN; 3], [i64::MAX; 3]),
+            3 * (u64::MAX as i128)
+        );
+    }
+}
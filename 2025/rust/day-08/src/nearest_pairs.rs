@@ -0,0 +1,271 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    metric::Metric,
+    part1::{JunctionBox, NearestNeighboursInfo},
+};
+
+/// Maximum number of points stored in a leaf of the k-d tree
+const LEAF_SIZE: usize = 8;
+
+/// Lazily generates every unordered pair of junction boxes in increasing
+/// order of distance as measured by the chosen [`Metric`], with pairs at the
+/// same distance in order of their indices.
+///
+/// Each box runs its own incremental nearest neighbour search over a k-d tree
+/// (only looking at boxes with a higher index so each pair is found once) and
+/// a heap holds the next candidate from each search. Only as much of each
+/// search is done as is needed for the pairs actually taken from the
+/// iterator.
+pub struct NearestPairs<'a> {
+    points: &'a [JunctionBox],
+    metric: Metric,
+    tree: KdTree,
+    searches: Vec<NeighbourSearch>,
+    candidates: BinaryHeap<Reverse<NearestNeighboursInfo>>,
+}
+
+impl<'a> NearestPairs<'a> {
+    pub fn new(points: &'a [JunctionBox], metric: Metric) -> Self {
+        let tree = KdTree::new(points);
+        let mut searches: Vec<NeighbourSearch> = (0..points.len())
+            .map(|query| NeighbourSearch::new(query, &tree))
+            .collect();
+        let mut candidates = BinaryHeap::new();
+        for search in searches.iter_mut() {
+            if let Some(candidate) = search.next(&tree, points, metric) {
+                candidates.push(Reverse(candidate));
+            }
+        }
+        Self {
+            points,
+            metric,
+            tree,
+            searches,
+            candidates,
+        }
+    }
+}
+
+impl Iterator for NearestPairs<'_> {
+    type Item = NearestNeighboursInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(result) = self.candidates.pop()?;
+        let query = result.junction_box_indices[0];
+        if let Some(candidate) = self.searches[query].next(&self.tree, self.points, self.metric) {
+            self.candidates.push(Reverse(candidate));
+        }
+        Some(result)
+    }
+}
+
+struct KdTree {
+    nodes: Vec<KdNode>,
+    /// Indices of the points arranged so each leaf owns a contiguous range
+    order: Vec<usize>,
+}
+
+struct KdNode {
+    /// Bounding box of all points under this node
+    min: [i64; 3],
+    max: [i64; 3],
+    contents: NodeContents,
+}
+
+enum NodeContents {
+    Leaf { start: usize, end: usize },
+    Branch { left: usize, right: usize },
+}
+
+impl KdTree {
+    const ROOT: usize = 0;
+
+    fn new(points: &[JunctionBox]) -> Self {
+        let mut result = Self {
+            nodes: vec![],
+            order: (0..points.len()).collect(),
+        };
+        if !points.is_empty() {
+            result.build(points, 0, points.len());
+        }
+        result
+    }
+
+    /// Builds the node for `order[start..end]` and returns its index
+    fn build(&mut self, points: &[JunctionBox], start: usize, end: usize) -> usize {
+        let mut min = [i64::MAX; 3];
+        let mut max = [i64::MIN; 3];
+        for &i in self.order[start..end].iter() {
+            for (axis, value) in points[i].coords().into_iter().enumerate() {
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(KdNode {
+            min,
+            max,
+            contents: NodeContents::Leaf { start, end },
+        });
+        if end - start > LEAF_SIZE {
+            // Split on the median of the widest axis
+            let axis = (0..3)
+                .max_by_key(|&axis| max[axis] as i128 - min[axis] as i128)
+                .expect("there are 3 axes");
+            let mid = (start + end) / 2;
+            self.order[start..end]
+                .select_nth_unstable_by_key(mid - start, |&i| points[i].coords()[axis]);
+            let left = self.build(points, start, mid);
+            let right = self.build(points, mid, end);
+            self.nodes[index].contents = NodeContents::Branch { left, right };
+        }
+        index
+    }
+
+    /// Smallest possible distance from `point` to anything inside `node`
+    fn lower_bound(&self, node: usize, point: &JunctionBox, metric: Metric) -> i128 {
+        let node = &self.nodes[node];
+        let closest: JunctionBox = point
+            .coords()
+            .into_iter()
+            .enumerate()
+            .map(|(axis, value)| value.clamp(node.min[axis], node.max[axis]))
+            .collect();
+        point.distance_to(&closest, metric)
+    }
+}
+
+/// Incremental nearest neighbour search for a single point
+struct NeighbourSearch {
+    query: usize,
+    queue: BinaryHeap<Reverse<SearchEntry>>,
+}
+
+impl NeighbourSearch {
+    fn new(query: usize, tree: &KdTree) -> Self {
+        let mut queue = BinaryHeap::new();
+        if !tree.nodes.is_empty() {
+            queue.push(Reverse(SearchEntry {
+                distance: 0,
+                item: SearchItem::Node(KdTree::ROOT),
+            }));
+        }
+        Self { query, queue }
+    }
+
+    /// Finds the next closest point with a higher index than the query point
+    fn next(
+        &mut self,
+        tree: &KdTree,
+        points: &[JunctionBox],
+        metric: Metric,
+    ) -> Option<NearestNeighboursInfo> {
+        let query_point = &points[self.query];
+        while let Some(Reverse(entry)) = self.queue.pop() {
+            match entry.item {
+                SearchItem::Point(other) => {
+                    return Some(NearestNeighboursInfo {
+                        distance: entry.distance,
+                        junction_box_indices: [self.query, other],
+                    });
+                }
+                SearchItem::Node(node) => match tree.nodes[node].contents {
+                    NodeContents::Leaf { start, end } => {
+                        for &other in tree.order[start..end].iter() {
+                            if other > self.query {
+                                self.queue.push(Reverse(SearchEntry {
+                                    distance: query_point.distance_to(&points[other], metric),
+                                    item: SearchItem::Point(other),
+                                }));
+                            }
+                        }
+                    }
+                    NodeContents::Branch { left, right } => {
+                        for child in [left, right] {
+                            self.queue.push(Reverse(SearchEntry {
+                                distance: tree.lower_bound(child, query_point, metric),
+                                item: SearchItem::Node(child),
+                            }));
+                        }
+                    }
+                },
+            }
+        }
+        None
+    }
+}
+
+/// Nodes come first so that at equal distances every node that could still
+/// hold a point at that distance is expanded before any point is returned.
+/// This keeps points at the same distance in order of index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SearchItem {
+    Node(usize),
+    Point(usize),
+}
+
+/// Entry in the search queue ordered by distance (or lower bound on it for
+/// nodes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SearchEntry {
+    distance: i128,
+    item: SearchItem,
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+    use crate::metric::METRICS;
+
+    fn all_pairs_sorted(points: &[JunctionBox], metric: Metric) -> Vec<NearestNeighboursInfo> {
+        let mut result = vec![];
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                result.push(NearestNeighboursInfo {
+                    distance: points[i].distance_to(&points[j], metric),
+                    junction_box_indices: [i, j],
+                });
+            }
+        }
+        result.sort();
+        result
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_matches_all_pairs_sorted(
+            // Small range so there are some repeated coordinates
+            coords in vec([0..50i64, 0..50i64, 0..50i64], 0..200)
+        ) {
+            let points: Vec<JunctionBox> = coords
+                .into_iter()
+                .map(|coords| coords.into_iter().collect())
+                .collect();
+            for (_, metric) in METRICS {
+                let actual: Vec<NearestNeighboursInfo> =
+                    NearestPairs::new(&points, *metric).collect();
+                prop_assert_eq!(actual, all_pairs_sorted(&points, *metric), "{}", metric);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ties_are_ordered_by_index() {
+        // Lattice so most distances are shared by many pairs
+        let points: Vec<JunctionBox> = (0..125)
+            .map(|i| [i % 5, (i / 5) % 5, i / 25].into_iter().collect())
+            .rev()
+            .collect();
+
+        for (_, metric) in METRICS {
+            let actual: Vec<NearestNeighboursInfo> = NearestPairs::new(&points, *metric).collect();
+            assert_eq!(actual, all_pairs_sorted(&points, *metric), "{metric}");
+        }
+    }
+}
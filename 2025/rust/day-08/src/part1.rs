@@ -1,44 +1,35 @@
-use core::f64;
 use std::{
     cmp::Reverse,
     collections::{BTreeSet, BinaryHeap},
 };
 
-use miette::Context;
+use miette::{Context, IntoDiagnostic, bail};
+
+use crate::{metric::Metric, nearest_pairs::NearestPairs};
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    process_with_metric(input, Metric::default())
+}
+
+/// Same as [`process`] but with the distance between boxes measured by `metric`
+#[tracing::instrument]
+pub fn process_with_metric(input: &str, metric: Metric) -> miette::Result<String> {
     const NUM_LARGEST_TO_CONSIDER: usize = 3;
     const BOXES_TO_CONNECT: usize = if cfg!(debug_assertions) { 10 } else { 1000 };
     dbg!(BOXES_TO_CONNECT);
 
-    // Convert input into points
-    let points: Vec<JunctionBox> = input
-        .lines()
-        .map(|line| {
-            line.split(",")
-                .map(|value| value.parse::<i64>().expect("failed to convert number"))
-                .collect()
-        })
-        .collect();
-
-    // Find nearest connections
-    let mut nearest_neighbours = get_nearest_neighbours(&points);
+    let points = parse_junction_boxes(input)?;
 
-    // Join and track with union find
-    let mut links: BTreeSet<NearestNeighboursInfo> = BTreeSet::new();
+    // Join the nearest pairs and track with union find
     let mut union_find = UnionFind::new(points.len());
-    while links.len() < BOXES_TO_CONNECT {
-        let next_candidate = nearest_neighbours
-            .pop()
-            .map(|Reverse(x)| x)
-            .wrap_err("out of connections before meeting quota")?;
-        if links.contains(&next_candidate) {
-            // Already linked
-            continue;
-        }
+    let mut links = 0;
+    for next_candidate in NearestPairs::new(&points, metric).take(BOXES_TO_CONNECT) {
         union_find.join_pair(next_candidate.junction_box_indices);
-        links.insert(next_candidate);
+        links += 1;
+    }
+    if links < BOXES_TO_CONNECT {
+        bail!("out of connections before meeting quota");
     }
 
     // Calculate output
@@ -67,24 +58,40 @@ pub fn process(input: &str) -> miette::Result<String> {
         .to_string())
 }
 
-pub fn get_nearest_neighbours(
-    points: &[JunctionBox],
-) -> BinaryHeap<Reverse<NearestNeighboursInfo>> {
-    let mut result = BinaryHeap::new();
-    for (i1, p1) in points.iter().enumerate() {
-        for (i2, p2) in points.iter().enumerate() {
-            if i1 != i2 {
-                let junction_box_indices = if i1 < i2 { [i1, i2] } else { [i2, i1] };
-                let distance = p1.distance_to(p2);
-                let info = NearestNeighboursInfo {
-                    distance,
-                    junction_box_indices,
-                };
-                result.push(Reverse(info));
+/// Largest absolute value allowed for a coordinate. Deltas are then at most
+/// 2^62 so the sum of the three squared deltas of [`Metric::Euclidean`] fits
+/// in an `i128`.
+pub const MAX_COORDINATE: i64 = 1 << 61;
+
+/// Converts each line of `x,y,z` into a junction box. Fails if a coordinate is
+/// not a number or is outside of +/- [`MAX_COORDINATE`].
+pub fn parse_junction_boxes(input: &str) -> miette::Result<Vec<JunctionBox>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let coords = line
+                .split(",")
+                .map(|value| {
+                    let value = value
+                        .parse::<i64>()
+                        .into_diagnostic()
+                        .wrap_err_with(|| format!("invalid number on line {}", i + 1))?;
+                    if value.unsigned_abs() > MAX_COORDINATE as u64 {
+                        bail!(
+                            "coordinate {value} on line {} is outside of +/- {MAX_COORDINATE}",
+                            i + 1
+                        );
+                    }
+                    Ok(value)
+                })
+                .collect::<miette::Result<Vec<i64>>>()?;
+            if coords.len() != 3 {
+                bail!("expected x,y,z on line {}", i + 1);
             }
-        }
-    }
-    result
+            Ok(coords.into_iter().collect())
+        })
+        .collect()
 }
 
 pub struct UnionFind {
@@ -138,17 +145,24 @@ pub struct JunctionBox {
     pub z: i64,
 }
 impl JunctionBox {
-    fn distance_to(&self, other_point: &JunctionBox) -> f64 {
-        ((self.x as f64 - other_point.x as f64).powi(2)
-            + (self.y as f64 - other_point.y as f64).powi(2)
-            + (self.z as f64 - other_point.z as f64).powi(2))
-        .sqrt()
+    #[must_use]
+    pub fn coords(&self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// Exact distance as measured by `metric` (see [`Metric::distance`])
+    #[must_use]
+    pub fn distance_to(&self, other_point: &JunctionBox, metric: Metric) -> i128 {
+        metric.distance(self.coords(), other_point.coords())
     }
 }
 
-#[derive(Debug)]
+/// Ordered by distance and then by the pair of indices so that every pair is
+/// distinct and the order of pairs at the same distance is deterministic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct NearestNeighboursInfo {
-    distance: f64,
+    /// See [`Metric::distance`]
+    pub distance: i128,
     // pair of indices with lower index number first
     pub junction_box_indices: [usize; 2],
 }
@@ -156,40 +170,12 @@ pub struct NearestNeighboursInfo {
 impl Default for NearestNeighboursInfo {
     fn default() -> Self {
         Self {
-            distance: f64::MAX,
+            distance: i128::MAX,
             junction_box_indices: Default::default(),
         }
     }
 }
 
-impl PartialEq for NearestNeighboursInfo {
-    fn eq(&self, other: &Self) -> bool {
-        debug_assert!(self.junction_box_indices[0] < self.junction_box_indices[1]);
-        debug_assert!(other.junction_box_indices[0] < other.junction_box_indices[1]);
-        debug_assert!(
-            self.junction_box_indices != other.junction_box_indices
-                || self.distance == other.distance
-        );
-        self.junction_box_indices == other.junction_box_indices
-    }
-}
-
-impl Eq for NearestNeighboursInfo {}
-
-impl PartialOrd for NearestNeighboursInfo {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for NearestNeighboursInfo {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.distance
-            .partial_cmp(&other.distance)
-            .expect("all f64 used are expected to be comparable")
-    }
-}
-
 impl FromIterator<i64> for JunctionBox {
     fn from_iter<T: IntoIterator<Item = i64>>(iter: T) -> Self {
         let mut iter = iter.into_iter();
@@ -231,4 +217,17 @@ mod tests {
         assert_eq!(process(input)?, "40");
         Ok(())
     }
+
+    #[test]
+    fn test_coordinate_limits() -> miette::Result<()> {
+        let points =
+            parse_junction_boxes(&format!("{0},{0},{0}\n-{0},-{0},-{0}\n", MAX_COORDINATE))?;
+        let distance = points[0].distance_to(&points[1], Metric::Euclidean);
+        assert_eq!(distance, 3 * (2 * MAX_COORDINATE as i128).pow(2));
+        assert!(parse_junction_boxes(&format!("0,{},0\n", MAX_COORDINATE + 1)).is_err());
+        assert!(parse_junction_boxes("-9223372036854775808,0,0\n").is_err());
+        assert!(parse_junction_boxes("0,0\n").is_err());
+        assert!(parse_junction_boxes("0,x,0\n").is_err());
+        Ok(())
+    }
 }
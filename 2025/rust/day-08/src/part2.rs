@@ -1,45 +1,28 @@
-use std::{cmp::Reverse, collections::BTreeSet};
+use miette::bail;
 
-use miette::Context;
-
-use crate::part1::{JunctionBox, NearestNeighboursInfo, UnionFind, get_nearest_neighbours};
+use crate::{metric::Metric, part1::parse_junction_boxes, spanning_forest::SpanningForest};
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    // Convert input into points
-    let points: Vec<JunctionBox> = input
-        .lines()
-        .map(|line| {
-            line.split(",")
-                .map(|value| value.parse::<i64>().expect("failed to convert number"))
-                .collect()
-        })
-        .collect();
-
-    // Find nearest connections
-    let mut nearest_neighbours = get_nearest_neighbours(&points);
+    process_with_metric(input, Metric::default())
+}
 
-    // Join and track with union find
-    let mut links: BTreeSet<NearestNeighboursInfo> = BTreeSet::new();
-    let mut union_find = UnionFind::new(points.len());
-    loop {
-        let next_candidate = nearest_neighbours
-            .pop()
-            .map(|Reverse(x)| x)
-            .wrap_err("out of connections before meeting quota")?;
-        if links.contains(&next_candidate) {
-            // Already linked
-            continue;
-        }
-        union_find.join_pair(next_candidate.junction_box_indices);
-        if union_find.group_size(next_candidate.junction_box_indices[0]) == points.len() {
-            // All points are now connected
-            let x1 = points[next_candidate.junction_box_indices[0]].x;
-            let x2 = points[next_candidate.junction_box_indices[1]].x;
-            return Ok((x1 * x2).to_string());
-        }
-        links.insert(next_candidate);
+/// Same as [`process`] but with the distance between boxes measured by `metric`
+#[tracing::instrument]
+pub fn process_with_metric(input: &str, metric: Metric) -> miette::Result<String> {
+    let forest = SpanningForest::new(parse_junction_boxes(input)?, metric);
+    if forest.edges().len() + 1 != forest.points().len() {
+        bail!("out of connections before all junction boxes were connected")
     }
+
+    // The last edge of the spanning tree is the one that connects everything
+    let Some(last) = forest.edges().last() else {
+        bail!("need at least two junction boxes to make a connection")
+    };
+    let x1 = forest.points()[last.junction_box_indices[0]].x;
+    let x2 = forest.points()[last.junction_box_indices[1]].x;
+    // Coordinates can be up to 2^61 so the product needs more than an i64
+    Ok((i128::from(x1) * i128::from(x2)).to_string())
 }
 
 #[cfg(test)]
@@ -72,4 +55,12 @@ mod tests {
         assert_eq!(process(input)?, "25272");
         Ok(())
     }
+
+    #[test]
+    fn test_large_coordinates() -> miette::Result<()> {
+        let far = crate::part1::MAX_COORDINATE;
+        let input = format!("{far},0,0\n-{far},1,0\n");
+        assert_eq!(process(&input)?, (-(far as i128) * far as i128).to_string());
+        Ok(())
+    }
 }
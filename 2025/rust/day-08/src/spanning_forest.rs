@@ -0,0 +1,275 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    metric::Metric,
+    nearest_pairs::NearestPairs,
+    part1::{JunctionBox, UnionFind},
+};
+
+/// Minimum spanning forest of the junction boxes built by connecting the
+/// nearest pairs in order (Kruskal's algorithm).
+pub struct SpanningForest {
+    points: Vec<JunctionBox>,
+    metric: Metric,
+    edges: Vec<ForestEdge>,
+}
+
+/// A connection that joined two separate circuits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForestEdge {
+    /// Position of this pair among all pairs connected (including the ones
+    /// that were already in the same circuit), starting from 0
+    pub connection: usize,
+    /// See [`Metric::distance`]
+    pub distance: i128,
+    /// Actual distance between the boxes
+    pub length: f64,
+    // pair of indices with lower index number first
+    pub junction_box_indices: [usize; 2],
+}
+
+impl SpanningForest {
+    pub fn new(points: Vec<JunctionBox>, metric: Metric) -> Self {
+        let mut edges = vec![];
+        let mut union_find = UnionFind::new(points.len());
+        let mut circuits = points.len();
+        for (connection, pair) in NearestPairs::new(&points, metric).enumerate() {
+            if circuits <= 1 {
+                break;
+            }
+            let [a, b] = pair.junction_box_indices;
+            if union_find.find(a) == union_find.find(b) {
+                continue;
+            }
+            union_find.join(a, b);
+            circuits -= 1;
+            edges.push(ForestEdge {
+                connection,
+                distance: pair.distance,
+                length: metric.length(pair.distance),
+                junction_box_indices: pair.junction_box_indices,
+            });
+        }
+        Self {
+            points,
+            metric,
+            edges,
+        }
+    }
+
+    #[must_use]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    #[must_use]
+    pub fn points(&self) -> &[JunctionBox] {
+        &self.points
+    }
+
+    /// Edges of the forest in the order they were added
+    #[must_use]
+    pub fn edges(&self) -> &[ForestEdge] {
+        &self.edges
+    }
+
+    /// Edges that are part of the circuits after the first `connections`
+    /// pairs have been connected
+    pub fn edges_after(&self, connections: usize) -> impl Iterator<Item = &ForestEdge> {
+        self.edges
+            .iter()
+            .take_while(move |edge| edge.connection < connections)
+    }
+
+    /// The circuits after the first `connections` pairs have been connected.
+    ///
+    /// Each circuit lists its junction box indices in increasing order and the
+    /// circuits are ordered largest first (then by lowest index).
+    #[must_use]
+    pub fn circuits_after(&self, connections: usize) -> Vec<Vec<usize>> {
+        let mut union_find = UnionFind::new(self.points.len());
+        for edge in self.edges_after(connections) {
+            union_find.join_pair(edge.junction_box_indices);
+        }
+        let mut by_root: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for index in 0..self.points.len() {
+            by_root
+                .entry(union_find.find(index))
+                .or_default()
+                .push(index);
+        }
+        let mut result: Vec<Vec<usize>> = by_root.into_values().collect();
+        result.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+        result
+    }
+
+    /// JSON with the junction boxes, every forest edge and the circuits after
+    /// the first `connections` pairs (all of them if `None`).
+    ///
+    /// Distances are written as strings since they can be far larger than
+    /// 2^53, past which JSON numbers are not reliably exact.
+    #[must_use]
+    pub fn to_json(&self, connections: Option<usize>) -> String {
+        let connections = connections.unwrap_or(usize::MAX);
+        let mut result = format!(
+            "{{\n  \"metric\": \"{}\",\n  \"junction_boxes\": [",
+            self.metric
+        );
+        for (i, point) in self.points.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let [x, y, z] = point.coords();
+            write!(result, "{separator}\n    [{x}, {y}, {z}]").unwrap();
+        }
+        result.push_str("\n  ],\n  \"edges\": [");
+        for (i, edge) in self.edges.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let [from, to] = edge.junction_box_indices;
+            write!(
+                result,
+                "{separator}\n    {{\"connection\": {}, \"from\": {from}, \"to\": {to}, \
+                 \"distance\": \"{}\", \"length\": {}}}",
+                edge.connection, edge.distance, edge.length
+            )
+            .unwrap();
+        }
+        result.push_str("\n  ],\n  \"circuits\": [");
+        for (i, circuit) in self.circuits_after(connections).iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let members: Vec<String> = circuit.iter().map(|index| index.to_string()).collect();
+            write!(result, "{separator}\n    [{}]", members.join(", ")).unwrap();
+        }
+        result.push_str("\n  ]\n}\n");
+        result
+    }
+
+    /// Graphviz DOT with one cluster per circuit after the first
+    /// `connections` pairs (all of them if `None`) and the forest edges in
+    /// those circuits labelled with their length
+    #[must_use]
+    pub fn to_dot(&self, connections: Option<usize>) -> String {
+        let connections = connections.unwrap_or(usize::MAX);
+        let mut result = String::from("graph circuits {\n  node [shape=box];\n");
+        for (i, circuit) in self.circuits_after(connections).iter().enumerate() {
+            writeln!(result, "  subgraph cluster_{i} {{").unwrap();
+            writeln!(
+                result,
+                "    label=\"circuit {i} ({} boxes)\";",
+                circuit.len()
+            )
+            .unwrap();
+            for &index in circuit {
+                let [x, y, z] = self.points[index].coords();
+                writeln!(result, "    {index} [label=\"{index}: {x},{y},{z}\"];").unwrap();
+            }
+            result.push_str("  }\n");
+        }
+        for edge in self.edges_after(connections) {
+            let [from, to] = edge.junction_box_indices;
+            writeln!(result, "  {from} -- {to} [label=\"{:.2}\"];", edge.length).unwrap();
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_junction_boxes;
+
+    const EXAMPLE: &str = "162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689
+";
+
+    #[test]
+    fn test_forest() -> miette::Result<()> {
+        let forest = SpanningForest::new(parse_junction_boxes(EXAMPLE)?, Metric::default());
+        assert_eq!(forest.edges().len(), 19);
+        assert_eq!(forest.edges()[0].junction_box_indices, [0, 19]);
+        assert!(
+            forest
+                .edges()
+                .windows(2)
+                .all(|pair| pair[0].connection < pair[1].connection
+                    && pair[0].distance <= pair[1].distance)
+        );
+
+        let sizes: Vec<usize> = forest.circuits_after(10).iter().map(Vec::len).collect();
+        assert_eq!(sizes[..3], [5, 4, 2]);
+        assert_eq!(sizes.iter().sum::<usize>(), 20);
+        assert_eq!(forest.circuits_after(usize::MAX).len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_metric_changes_first_edge() {
+        let points = || parse_junction_boxes("0,0,0\n3,3,3\n5,0,0\n").expect("valid input");
+        let first_edge =
+            |metric| SpanningForest::new(points(), metric).edges()[0].junction_box_indices;
+        assert_eq!(first_edge(Metric::Euclidean), [1, 2]);
+        assert_eq!(first_edge(Metric::Manhattan), [0, 2]);
+        assert_eq!(first_edge(Metric::Chebyshev), [0, 1]);
+    }
+
+    #[test]
+    fn test_exports() -> miette::Result<()> {
+        let points = parse_junction_boxes("0,0,0\n3,4,0\n100,0,0\n")?;
+        let forest = SpanningForest::new(points, Metric::Euclidean);
+        assert_eq!(
+            forest.to_json(Some(1)),
+            r#"{
+  "metric": "euclidean",
+  "junction_boxes": [
+    [0, 0, 0],
+    [3, 4, 0],
+    [100, 0, 0]
+  ],
+  "edges": [
+    {"connection": 0, "from": 0, "to": 1, "distance": "25", "length": 5},
+    {"connection": 1, "from": 1, "to": 2, "distance": "9425", "length": 97.082439194738}
+  ],
+  "circuits": [
+    [0, 1],
+    [2]
+  ]
+}
+"#
+        );
+        assert_eq!(
+            forest.to_dot(Some(1)),
+            r#"graph circuits {
+  node [shape=box];
+  subgraph cluster_0 {
+    label="circuit 0 (2 boxes)";
+    0 [label="0: 0,0,0"];
+    1 [label="1: 3,4,0"];
+  }
+  subgraph cluster_1 {
+    label="circuit 1 (1 boxes)";
+    2 [label="2: 100,0,0"];
+  }
+  0 -- 1 [label="5.00"];
+}
+"#
+        );
+        Ok(())
+    }
+}
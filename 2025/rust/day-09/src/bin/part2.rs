@@ -1,12 +1,31 @@
-use day_09::part2::process;
-use miette::Context;
+use day_09::{part1::parse_red_squares, part2::process, svg};
+use miette::{Context, IntoDiagnostic, bail};
+
+const USAGE: &str = "usage: part2 [--svg PATH [--rejected]]
+
+  --svg PATH    write an SVG of the red squares and the largest rectangle
+                found by each part to PATH
+  --rejected    also draw every rectangle rejected for not being inside";
 
 #[tracing::instrument]
 fn main() -> miette::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input2.txt");
-    let result = process(file).context("process part 2")?;
-    println!("{}", result);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => {
+            let result = process(file).context("process part 2")?;
+            println!("{}", result);
+        }
+        ["--svg", path] | ["--svg", path, "--rejected"] => {
+            let show_rejected = args.len() == 3;
+            let image = svg::render(&parse_red_squares(file), show_rejected)?;
+            std::fs::write(path, image)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to write {path:?}"))?;
+        }
+        _ => bail!("unexpected arguments {args:?}\n{USAGE}"),
+    }
     Ok(())
 }
@@ -1,9 +1,107 @@
+use std::cmp::Reverse;
+
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let mut result = 0;
+    let red_squares = parse_red_squares(input);
+    let result = largest_rectangle(&red_squares)
+        .map(|[i, j]| get_area(&red_squares[i], &red_squares[j]))
+        .unwrap_or_default();
+    Ok(result.to_string())
+}
+
+/// Indices of the red squares at opposite corners of the largest rectangle
+/// (the lowest pair of indices if there is a tie)
+pub fn largest_rectangle(red_squares: &[(i64, i64)]) -> Option<[usize; 2]> {
+    // The corners are usually convex hull vertices so try those pairs first
+    let hull = convex_hull(red_squares);
+    let mut best = hull
+        .iter()
+        .enumerate()
+        .flat_map(|(k, &i)| {
+            hull[k + 1..]
+                .iter()
+                .map(move |&j| candidate(red_squares, i, j))
+        })
+        .max();
+
+    // But not always, a square just inside a diagonal edge of the hull can
+    // still be a corner (see `test_corner_inside_hull`). So every other square
+    // that could beat the best so far with the far corner of the bounding box
+    // is checked against every square
+    let min_x = red_squares.iter().map(|square| square.0).min()?;
+    let max_x = red_squares.iter().map(|square| square.0).max()?;
+    let min_y = red_squares.iter().map(|square| square.1).min()?;
+    let max_y = red_squares.iter().map(|square| square.1).max()?;
+    let mut on_hull = vec![false; red_squares.len()];
+    for &i in hull.iter() {
+        on_hull[i] = true;
+    }
+    for (i, square) in red_squares.iter().enumerate() {
+        let length = (square.0 - min_x).max(max_x - square.0) + 1;
+        let width = (square.1 - min_y).max(max_y - square.1) + 1;
+        let largest_possible = length as u64 * width as u64;
+        if on_hull[i] || best.is_some_and(|(area, _)| largest_possible < area) {
+            continue;
+        }
+        let best_with_i = (0..red_squares.len())
+            .filter(|&j| j != i)
+            .map(|j| candidate(red_squares, i, j))
+            .max();
+        best = best.max(best_with_i);
+    }
+
+    best.map(|(_, Reverse(pair))| pair)
+}
+
+/// Checks every pair (gives the same result as [`largest_rectangle`])
+pub fn largest_rectangle_exhaustive(red_squares: &[(i64, i64)]) -> Option<[usize; 2]> {
+    (0..red_squares.len())
+        .flat_map(|i| (i + 1..red_squares.len()).map(move |j| candidate(red_squares, i, j)))
+        .max()
+        .map(|(_, Reverse(pair))| pair)
+}
+
+/// Orders the rectangle between red squares `i` and `j` by area and then
+/// lowest pair of indices first
+fn candidate(red_squares: &[(i64, i64)], i: usize, j: usize) -> (u64, Reverse<[usize; 2]>) {
+    let area = get_area(&red_squares[i], &red_squares[j]);
+    (area, Reverse([i.min(j), i.max(j)]))
+}
+
+/// Indices of the vertices of the convex hull (points on an edge are left out)
+fn convex_hull(points: &[(i64, i64)]) -> Vec<usize> {
+    let mut sorted: Vec<usize> = (0..points.len()).collect();
+    sorted.sort_by_key(|&i| points[i]);
+    sorted.dedup_by_key(|i| points[*i]);
+    if sorted.len() < 3 {
+        return sorted;
+    }
 
-    // Collect input
-    let red_squares = input
+    // Andrew's monotone chain (lower hull and then upper hull)
+    let turns_left = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        (b.0 - a.0) as i128 * (c.1 - a.1) as i128 - (b.1 - a.1) as i128 * (c.0 - a.0) as i128 > 0
+    };
+    let mut result: Vec<usize> = vec![];
+    for pass in [sorted.clone(), sorted.into_iter().rev().collect()] {
+        let start = result.len();
+        for i in pass {
+            while result.len() >= start + 2
+                && !turns_left(result[result.len() - 2], result[result.len() - 1], i)
+            {
+                result.pop();
+            }
+            result.push(i);
+        }
+        // Last point is the first point of the next pass
+        result.pop();
+    }
+    result
+}
+
+/// Converts each line of `x,y` into the position of a red square
+pub fn parse_red_squares(input: &str) -> Vec<(i64, i64)> {
+    input
         .lines()
         .map(|line| {
             let pair: Vec<i64> = line
@@ -13,19 +111,12 @@ pub fn process(input: &str) -> miette::Result<String> {
             debug_assert_eq!(pair.len(), 2);
             (pair[0], pair[1])
         })
-        .collect::<Vec<_>>();
-
-    // Check each pair (Room to optimize but may not be worth it)
-    for (i, first) in red_squares.iter().enumerate().take(red_squares.len() - 1) {
-        for second in red_squares.iter().skip(i + 1) {
-            result = result.max(get_area(first, second));
-        }
-    }
-
-    Ok(result.to_string())
+        .collect()
 }
 
-fn get_area(first: &(i64, i64), second: &(i64, i64)) -> u64 {
+/// Number of tiles in the rectangle with `first` and `second` as opposite
+/// corners
+pub fn get_area(first: &(i64, i64), second: &(i64, i64)) -> u64 {
     let length = (first.0 - second.0).abs() + 1;
     let width = (first.1 - second.1).abs() + 1;
     length as u64 * width as u64
@@ -33,6 +124,8 @@ fn get_area(first: &(i64, i64), second: &(i64, i64)) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    use proptest::{collection::vec, prelude::*, sample::select};
+
     use super::*;
 
     #[test]
@@ -49,4 +142,27 @@ mod tests {
         assert_eq!(process(input)?, "50");
         Ok(())
     }
+
+    #[test]
+    fn test_corner_inside_hull() {
+        let red_squares = parse_red_squares("0,1\n10,3\n4,6\n8,4\n");
+        assert_eq!(convex_hull(&red_squares).len(), 3);
+        assert_eq!(largest_rectangle(&red_squares), Some([0, 3]));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1000))]
+
+        #[test]
+        fn test_matches_exhaustive_on_random_squares(
+            // Mix of small ranges (lots of collinear points) and large ones
+            red_squares in select(vec![4i64, 10, 50, 1000])
+                .prop_flat_map(|range| vec((0..range, 0..range), 1..=20))
+        ) {
+            prop_assert_eq!(
+                largest_rectangle(&red_squares),
+                largest_rectangle_exhaustive(&red_squares)
+            );
+        }
+    }
 }
@@ -1,65 +1,46 @@
-/// Based on https://www.youtube.com/watch?v=RyLuE5xFLxw
+use crate::{
+    part1::{get_area, parse_red_squares},
+    tile_region::TileRegion,
+};
+
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let mut result = 0;
-
-    // Collect input
-    let red_squares = input
-        .lines()
-        .map(|line| {
-            let pair: Vec<i64> = line
-                .split(",")
-                .map(|val| val.parse().expect("failed to parse number"))
-                .collect();
-            debug_assert_eq!(pair.len(), 2);
-            (pair[0], pair[1])
-        })
-        .collect::<Vec<_>>();
+    let red_squares = parse_red_squares(input);
+    let region = TileRegion::new(&red_squares)?;
+    let result = largest_rectangle(&red_squares, &region)
+        .map(|[i, j]| get_area(&red_squares[i], &red_squares[j]))
+        .unwrap_or_default();
+    Ok(result.to_string())
+}
 
-    // Get list of lines
-    let mut lines = vec![(red_squares.first().unwrap(), red_squares.last().unwrap())];
-    for i in 0..red_squares.len() - 1 {
-        lines.push((&red_squares[i], &red_squares[i + 1]));
-    }
+/// Indices of the red squares at opposite corners of the largest rectangle
+/// that is inside `region`
+pub fn largest_rectangle(red_squares: &[(i64, i64)], region: &TileRegion) -> Option<[usize; 2]> {
+    let mut result = None;
+    let mut largest_area = 0;
 
     // Check each pair
-    for (i, first) in red_squares.iter().enumerate().take(red_squares.len() - 1) {
-        for second in red_squares.iter().skip(i + 1) {
-            // Check for overlap
-            if !overlaps_line(first, second, &lines) {
-                result = result.max(get_area(first, second));
+    for (i, first) in red_squares.iter().enumerate() {
+        for (j, second) in red_squares.iter().enumerate().skip(i + 1) {
+            let area = get_area(first, second);
+            if area > largest_area && region.contains_rectangle(i, j) {
+                largest_area = area;
+                result = Some([i, j]);
             }
         }
     }
 
-    Ok(result.to_string())
-}
-
-#[expect(clippy::type_complexity)]
-fn overlaps_line(
-    first: &(i64, i64),
-    second: &(i64, i64),
-    lines: &[(&(i64, i64), &(i64, i64))],
-) -> bool {
-    // Overlaps a line if it is both above and below and to the left and to the
-    // right. That means it must be crossing the line
-    for (line_start, line_end) in lines {
-        let is_to_left = first.0.max(second.0) <= line_start.0.min(line_end.0);
-        let is_to_right = first.0.min(second.0) >= line_start.0.max(line_end.0);
-        let is_above = first.1.max(second.1) <= line_start.1.min(line_end.1);
-        let is_below = first.1.min(second.1) >= line_start.1.max(line_end.1);
-        if !is_to_left && !is_to_right && !is_above && !is_below {
-            return true;
-        }
-    }
-
-    false
+    result
 }
 
-fn get_area(first: &(i64, i64), second: &(i64, i64)) -> u64 {
-    let length = (first.0 - second.0).abs() + 1;
-    let width = (first.1 - second.1).abs() + 1;
-    length as u64 * width as u64
+/// Pairs of red squares whose rectangle is not inside `region`
+pub fn rejected_rectangles(
+    red_squares: &[(i64, i64)],
+    region: &TileRegion,
+) -> impl Iterator<Item = [usize; 2]> {
+    (0..red_squares.len())
+        .flat_map(move |i| (i + 1..red_squares.len()).map(move |j| [i, j]))
+        .filter(|&[i, j]| !region.contains_rectangle(i, j))
 }
 
 #[cfg(test)]
@@ -0,0 +1,136 @@
+use std::fmt::Write;
+
+use miette::bail;
+
+use crate::{part1, part2, tile_region::TileRegion};
+
+const PART1_COLOUR: &str = "blue";
+const PART2_COLOUR: &str = "green";
+const REJECTED_COLOUR: &str = "red";
+
+/// Draws the loop of red squares with the largest rectangle found by each
+/// part and optionally every rectangle part 2 rejected for not being inside
+/// the loop.
+///
+/// Each tile is a 1x1 square with its top left corner at its coordinates.
+/// Fails if there are no red squares (there is nothing to size the image by).
+pub fn render(red_squares: &[(i64, i64)], show_rejected: bool) -> miette::Result<String> {
+    if red_squares.is_empty() {
+        bail!("no red squares to draw");
+    }
+    let region = TileRegion::new(red_squares)?;
+    let (min_x, max_x) = min_max(red_squares.iter().map(|square| square.0));
+    let (min_y, max_y) = min_max(red_squares.iter().map(|square| square.1));
+    let margin = ((max_x - min_x).max(max_y - min_y) / 50).max(1);
+
+    let mut result = String::new();
+    writeln!(
+        result,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 1 + 2 * margin,
+        max_y - min_y + 1 + 2 * margin
+    )
+    .unwrap();
+
+    if show_rejected {
+        writeln!(
+            result,
+            "  <g fill=\"none\" stroke=\"{REJECTED_COLOUR}\" stroke-opacity=\"0.1\">"
+        )
+        .unwrap();
+        for corners in part2::rejected_rectangles(red_squares, &region) {
+            result.push_str("    ");
+            write_rectangle(&mut result, red_squares, corners, "");
+        }
+        result.push_str("  </g>\n");
+    }
+
+    let points: Vec<String> = red_squares
+        .iter()
+        .map(|&(x, y)| format!("{},{}", x as f64 + 0.5, y as f64 + 0.5))
+        .collect();
+    writeln!(
+        result,
+        "  <polygon points=\"{}\" fill=\"lightgrey\" stroke=\"black\" \
+         vector-effect=\"non-scaling-stroke\"/>",
+        points.join(" ")
+    )
+    .unwrap();
+
+    for (corners, colour) in [
+        (part1::largest_rectangle(red_squares), PART1_COLOUR),
+        (part2::largest_rectangle(red_squares, &region), PART2_COLOUR),
+    ] {
+        if let Some(corners) = corners {
+            let style = format!(
+                " fill=\"{colour}\" fill-opacity=\"0.3\" stroke=\"{colour}\" \
+                 vector-effect=\"non-scaling-stroke\""
+            );
+            result.push_str("  ");
+            write_rectangle(&mut result, red_squares, corners, &style);
+        }
+    }
+
+    result.push_str("</svg>\n");
+    Ok(result)
+}
+
+/// Writes the rectangle covering every tile between the two red squares
+fn write_rectangle(
+    result: &mut String,
+    red_squares: &[(i64, i64)],
+    [first, second]: [usize; 2],
+    style: &str,
+) {
+    let (x1, y1) = red_squares[first];
+    let (x2, y2) = red_squares[second];
+    writeln!(
+        result,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{style}/>",
+        x1.min(x2),
+        y1.min(y2),
+        (x1 - x2).abs() + 1,
+        (y1 - y2).abs() + 1
+    )
+    .unwrap();
+}
+
+fn min_max(values: impl Iterator<Item = i64>) -> (i64, i64) {
+    values.fold((i64::MAX, i64::MIN), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_red_squares;
+
+    #[test]
+    fn test_render() -> miette::Result<()> {
+        let red_squares = parse_red_squares("7,1\n11,1\n11,7\n9,7\n9,5\n2,5\n2,3\n7,3\n");
+        let svg = render(&red_squares, false)?;
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"1 0 12 9\">"));
+        assert!(svg.contains("points=\"7.5,1.5 11.5,1.5 11.5,7.5 9.5,7.5"));
+        // Part 1: 2,5 to 11,1 and part 2: 9,5 to 2,3
+        assert!(svg.contains(&format!(
+            "<rect x=\"2\" y=\"1\" width=\"10\" height=\"5\" fill=\"{PART1_COLOUR}\""
+        )));
+        assert!(svg.contains(&format!(
+            "<rect x=\"2\" y=\"3\" width=\"8\" height=\"3\" fill=\"{PART2_COLOUR}\""
+        )));
+        assert_eq!(svg.matches("<rect").count(), 2);
+
+        let with_rejected = render(&red_squares, true)?;
+        assert!(with_rejected.matches("<rect").count() > 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let error = render(&[], false).expect_err("nothing to draw");
+        assert_eq!(error.to_string(), "no red squares to draw");
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use miette::bail;
+
+/// The tiles inside (or on the boundary of) the loop of red squares.
+///
+/// Coordinates are compressed so that each cell of the grid is a block of
+/// tiles that are either all inside or all outside: every red square's row and
+/// column gets its own cell and the tiles between two consecutive ones are
+/// merged into a single cell. Cells outside the loop are found with a flood
+/// fill from a padding border and counted with 2D prefix sums so checking if a
+/// rectangle is fully inside takes constant time.
+pub struct TileRegion {
+    /// Compressed column and row of each red square (in input order)
+    square_cells: Vec<(usize, usize)>,
+    width: usize,
+    /// `outside_prefix[y * (width + 1) + x]` is the number of outside cells
+    /// in the columns before `x` and rows before `y`
+    outside_prefix: Vec<u32>,
+}
+
+impl TileRegion {
+    /// Builds the region enclosed by `red_squares` where each square is
+    /// joined to the next one (and the last to the first) by a straight line
+    /// of tiles
+    pub fn new(red_squares: &[(i64, i64)]) -> miette::Result<Self> {
+        let columns = compress(red_squares.iter().map(|square| square.0));
+        let rows = compress(red_squares.iter().map(|square| square.1));
+        let width = columns.len();
+        let height = rows.len();
+        let square_cells: Vec<(usize, usize)> = red_squares
+            .iter()
+            .map(|square| (cell_of(&columns, square.0), cell_of(&rows, square.1)))
+            .collect();
+
+        // Draw the boundary
+        let mut is_boundary = vec![false; width * height];
+        for (i, &start) in square_cells.iter().enumerate() {
+            let end = square_cells[(i + 1) % square_cells.len()];
+            if start.0 != end.0 && start.1 != end.1 {
+                bail!(
+                    "red squares {:?} and {:?} are not in the same row or column",
+                    red_squares[i],
+                    red_squares[(i + 1) % red_squares.len()]
+                );
+            }
+            for x in start.0.min(end.0)..=start.0.max(end.0) {
+                for y in start.1.min(end.1)..=start.1.max(end.1) {
+                    is_boundary[y * width + x] = true;
+                }
+            }
+        }
+
+        // Flood fill from the padding (which is always outside)
+        let mut is_outside = vec![false; width * height];
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        if !is_outside.is_empty() {
+            is_outside[0] = true;
+            queue.push_back((0, 0));
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (next_x, next_y) in neighbours {
+                if next_x >= width || next_y >= height {
+                    continue;
+                }
+                let index = next_y * width + next_x;
+                if !is_outside[index] && !is_boundary[index] {
+                    is_outside[index] = true;
+                    queue.push_back((next_x, next_y));
+                }
+            }
+        }
+
+        let mut outside_prefix = vec![0; (width + 1) * (height + 1)];
+        for y in 0..height {
+            for x in 0..width {
+                outside_prefix[(y + 1) * (width + 1) + x + 1] = is_outside[y * width + x] as u32
+                    + outside_prefix[y * (width + 1) + x + 1]
+                    + outside_prefix[(y + 1) * (width + 1) + x]
+                    - outside_prefix[y * (width + 1) + x];
+            }
+        }
+
+        Ok(Self {
+            square_cells,
+            width,
+            outside_prefix,
+        })
+    }
+
+    /// Returns `true` if every tile of the rectangle with the red squares at
+    /// indices `first` and `second` as opposite corners is inside the region
+    #[must_use]
+    pub fn contains_rectangle(&self, first: usize, second: usize) -> bool {
+        let (x1, y1) = self.square_cells[first];
+        let (x2, y2) = self.square_cells[second];
+        let (left, right) = (x1.min(x2), x1.max(x2) + 1);
+        let (top, bottom) = (y1.min(y2), y1.max(y2) + 1);
+        let at = |x: usize, y: usize| self.outside_prefix[y * (self.width + 1) + x];
+        at(right, bottom) + at(left, top) == at(left, bottom) + at(right, top)
+    }
+}
+
+/// Returns the first tile of each cell along one axis
+///
+/// Includes a cell of padding before the smallest value and after the largest
+fn compress(values: impl Iterator<Item = i64>) -> Vec<i64> {
+    let mut values: Vec<i64> = values.collect();
+    values.sort_unstable();
+    values.dedup();
+    let mut result = vec![];
+    if let Some(first) = values.first() {
+        result.push(first - 1);
+    }
+    for (i, &value) in values.iter().enumerate() {
+        result.push(value);
+        if values.get(i + 1).is_none_or(|&next| next > value + 1) {
+            // Tiles between this value and the next (or the padding at the end)
+            result.push(value + 1);
+        }
+    }
+    result
+}
+
+fn cell_of(cell_starts: &[i64], value: i64) -> usize {
+    cell_starts
+        .binary_search(&value)
+        .expect("all red squares were included when compressing")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::{get_area, parse_red_squares};
+
+    /// Checks every tile directly
+    fn largest_brute_force(red_squares: &[(i64, i64)]) -> u64 {
+        let is_inside = |x: i64, y: i64| {
+            let mut crossings = 0;
+            for (i, start) in red_squares.iter().enumerate() {
+                let end = red_squares[(i + 1) % red_squares.len()];
+                let (min_x, max_x) = (start.0.min(end.0), start.0.max(end.0));
+                let (min_y, max_y) = (start.1.min(end.1), start.1.max(end.1));
+                if (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y) {
+                    return true;
+                }
+                // Count vertical edges crossed by a ray to the left of the
+                // tile's centre
+                if start.0 == end.0 && start.0 < x && min_y <= y && y < max_y {
+                    crossings += 1;
+                }
+            }
+            crossings % 2 == 1
+        };
+        let mut result = 0;
+        for first in red_squares {
+            for second in red_squares {
+                let all_inside = (first.0.min(second.0)..=first.0.max(second.0)).all(|x| {
+                    (first.1.min(second.1)..=first.1.max(second.1)).all(|y| is_inside(x, y))
+                });
+                if all_inside {
+                    result = result.max(get_area(first, second));
+                }
+            }
+        }
+        result
+    }
+
+    fn largest(red_squares: &[(i64, i64)]) -> miette::Result<u64> {
+        let region = TileRegion::new(red_squares)?;
+        let mut result = 0;
+        for i in 0..red_squares.len() {
+            for j in i..red_squares.len() {
+                if region.contains_rectangle(i, j) {
+                    result = result.max(get_area(&red_squares[i], &red_squares[j]));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    #[test]
+    fn test_concave() -> miette::Result<()> {
+        // U shape where the rectangle across the notch touches edges but
+        // is not crossed by any
+        let red_squares = parse_red_squares("0,0\n10,0\n10,10\n8,10\n8,1\n2,1\n2,10\n0,10\n");
+        assert_eq!(largest(&red_squares)?, 33);
+        assert_eq!(largest_brute_force(&red_squares), 33);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spiral() -> miette::Result<()> {
+        let red_squares = parse_red_squares(
+            "0,0\n16,0\n16,16\n4,16\n4,6\n10,6\n10,10\n8,10\n8,8\n6,8\n6,14\n14,14\n14,2\n0,2\n",
+        );
+        assert_eq!(largest(&red_squares)?, largest_brute_force(&red_squares));
+        Ok(())
+    }
+
+    #[test]
+    fn test_adjacent_edges() -> miette::Result<()> {
+        // Edges in neighbouring columns leave no tiles between them
+        let red_squares = parse_red_squares("0,0\n3,0\n3,5\n4,5\n4,0\n9,0\n9,9\n0,9\n");
+        assert_eq!(largest(&red_squares)?, largest_brute_force(&red_squares));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagonal_edge() {
+        assert!(TileRegion::new(&[(0, 0), (5, 5), (0, 5)]).is_err());
+    }
+}
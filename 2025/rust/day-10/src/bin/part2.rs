@@ -1,12 +1,26 @@
-use day_10::part2::process;
-use miette::Context;
+use day_10::part2::{explain, process};
+use miette::{Context, bail};
+
+const USAGE: &str = "usage: part2 [--explain]
+
+  --explain  print the presses for each machine (checked against the machine)";
 
 #[tracing::instrument]
 fn main() -> miette::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input2.txt");
-    let result = process(file).context("process part 2")?;
-    println!("{}", result);
+    match std::env::args().nth(1).as_deref() {
+        None => {
+            let result = process(file).context("process part 2")?;
+            println!("{}", result);
+        }
+        Some("--explain") => {
+            for explanation in explain(file).context("explain part 2")? {
+                println!("{explanation}");
+            }
+        }
+        Some(other) => bail!("unexpected argument {other:?}\n{USAGE}"),
+    }
     Ok(())
 }
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+/// The presses found for one machine and what they produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// Line of the input the machine is on (starting from 1)
+    pub line: usize,
+    /// Which lights or counters each button changes
+    pub buttons: Vec<Vec<usize>>,
+    /// How many times each button is pressed
+    pub presses: Vec<u64>,
+    /// The lights or joltage the presses produce (already checked to match
+    /// the machine)
+    pub outcome: String,
+}
+
+impl Explanation {
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.presses.iter().sum()
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: {} presses give {}",
+            self.line,
+            self.total(),
+            self.outcome
+        )?;
+        for (button, &count) in self.buttons.iter().zip(&self.presses) {
+            if count > 0 {
+                let indices: Vec<String> = button.iter().map(|i| i.to_string()).collect();
+                write!(f, "\n  ({}) x{count}", indices.join(","))?;
+            }
+        }
+        Ok(())
+    }
+}
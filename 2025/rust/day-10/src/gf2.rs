@@ -0,0 +1,343 @@
+use std::ops::BitXorAssign;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Most free variables [`min_weight_solution`] tries every combination of
+/// (2^24 combinations still finish in well under a second)
+const EXHAUSTIVE_FREE_VARIABLES: usize = 24;
+
+/// Vector over GF(2) packed into `u64` words (bit `i % 64` of word `i / 64` is
+/// entry `i`) so it can be any length
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    /// All zeros
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0; len.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// Ones at `indices` and zeros everywhere else
+    ///
+    /// # Panics
+    ///
+    /// If any index is not less than `len`
+    #[must_use]
+    pub fn from_indices(len: usize, indices: &[usize]) -> Self {
+        let mut result = Self::new(len);
+        for &index in indices {
+            result.set(index, true);
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "index {index} out of range {}", self.len);
+        self.words[index / WORD_BITS] >> (index % WORD_BITS) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "index {index} out of range {}", self.len);
+        let mask = 1 << (index % WORD_BITS);
+        if value {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Number of ones
+    #[must_use]
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Indices of the ones in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&index| self.get(index))
+    }
+
+    /// Number of indices that are one in both `self` and `other`
+    fn count_common_ones(&self, other: &BitVector) -> u32 {
+        debug_assert_eq!(self.len, other.len);
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(word, other)| (word & other).count_ones())
+            .sum()
+    }
+}
+
+impl BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, rhs: &BitVector) {
+        debug_assert_eq!(self.len, rhs.len);
+        for (word, rhs) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *word ^= rhs;
+        }
+    }
+}
+
+/// Finds `x` with the fewest ones such that the sum of the `columns` selected
+/// by `x` is `target` (`None` if there is no solution).
+///
+/// Gaussian elimination gives one solution and a basis for the null space (one
+/// vector per free variable). With up to 24 free variables every combination
+/// of the basis is visited in Gray code order so each step only needs one
+/// vector added. With more, the combinations are searched with branch and
+/// bound instead, which still finds the minimum but can take much longer.
+#[must_use]
+pub fn min_weight_solution(columns: &[BitVector], target: &BitVector) -> Option<BitVector> {
+    solve(columns, target, EXHAUSTIVE_FREE_VARIABLES)
+}
+
+/// [`min_weight_solution`] trying every combination with at most
+/// `max_exhaustive` free variables
+fn solve(columns: &[BitVector], target: &BitVector, max_exhaustive: usize) -> Option<BitVector> {
+    let num_rows = target.len();
+    let num_columns = columns.len();
+
+    // Each row is an equation with the right hand side stored in the last bit
+    let mut rows: Vec<BitVector> = (0..num_rows)
+        .map(|row| {
+            let mut equation = BitVector::new(num_columns + 1);
+            for (column, values) in columns.iter().enumerate() {
+                debug_assert_eq!(values.len(), num_rows);
+                equation.set(column, values.get(row));
+            }
+            equation.set(num_columns, target.get(row));
+            equation
+        })
+        .collect();
+
+    // Reduce to reduced row echelon form
+    let mut pivot_columns = vec![];
+    for column in 0..num_columns {
+        let rank = pivot_columns.len();
+        let Some(pivot) = (rank..num_rows).find(|&row| rows[row].get(column)) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        let (before, rest) = rows.split_at_mut(rank);
+        let (pivot_row, after) = rest.split_first_mut().expect("pivot row exists");
+        for row in before.iter_mut().chain(after.iter_mut()) {
+            if row.get(column) {
+                *row ^= pivot_row;
+            }
+        }
+        pivot_columns.push(column);
+    }
+    if rows[pivot_columns.len()..]
+        .iter()
+        .any(|row| row.get(num_columns))
+    {
+        // 0 = 1 so no solution
+        return None;
+    }
+
+    let mut solution = BitVector::new(num_columns);
+    for (row, &column) in pivot_columns.iter().enumerate() {
+        solution.set(column, rows[row].get(num_columns));
+    }
+
+    let mut is_pivot = vec![false; num_columns];
+    for &column in pivot_columns.iter() {
+        is_pivot[column] = true;
+    }
+    let null_basis: Vec<BitVector> = (0..num_columns)
+        .filter(|&column| !is_pivot[column])
+        .map(|free| {
+            let mut vector = BitVector::new(num_columns);
+            vector.set(free, true);
+            for (row, &column) in pivot_columns.iter().enumerate() {
+                vector.set(column, rows[row].get(free));
+            }
+            vector
+        })
+        .collect();
+
+    if null_basis.len() <= max_exhaustive {
+        Some(gray_code_search(solution, &null_basis))
+    } else {
+        Some(branch_and_bound(solution, &null_basis))
+    }
+}
+
+/// Lightest of `solution` plus every combination of `null_basis`
+fn gray_code_search(mut solution: BitVector, null_basis: &[BitVector]) -> BitVector {
+    let mut best = solution.clone();
+    for step in 1..1u64 << null_basis.len() {
+        // Gray code changes the bit at the position of the lowest one in step
+        solution ^= &null_basis[step.trailing_zeros() as usize];
+        if solution.count_ones() < best.count_ones() {
+            best = solution.clone();
+        }
+    }
+    best
+}
+
+/// Same result as [`gray_code_search`] but deciding one basis vector at a
+/// time and skipping every combination that starts with choices already
+/// giving at least as many ones as the best solution found so far
+fn branch_and_bound(solution: BitVector, null_basis: &[BitVector]) -> BitVector {
+    let num_columns = solution.len();
+    // Columns that first stop changing once each number of vectors is decided
+    // (just after the last vector with a one in that column)
+    let mut decided = vec![BitVector::new(num_columns); null_basis.len() + 1];
+    for column in 0..num_columns {
+        let depth = null_basis
+            .iter()
+            .rposition(|vector| vector.get(column))
+            .map_or(0, |i| i + 1);
+        decided[depth].set(column, true);
+    }
+    // Every column is in exactly one of them so xor accumulates the union
+    for depth in 1..decided.len() {
+        let (before, rest) = decided.split_at_mut(depth);
+        rest[0] ^= &before[depth - 1];
+    }
+
+    let mut search = Search {
+        null_basis,
+        decided,
+        best: solution.clone(),
+        current: solution,
+    };
+    search.run(0);
+    search.best
+}
+
+struct Search<'a> {
+    null_basis: &'a [BitVector],
+    /// Columns whose value is fixed once the first `i` vectors are decided
+    decided: Vec<BitVector>,
+    /// Solution with the vectors chosen so far added
+    current: BitVector,
+    best: BitVector,
+}
+
+impl Search<'_> {
+    /// Tries leaving out and then adding the vector at `depth` (and the ones
+    /// after it) as long as that could still beat the best solution
+    fn run(&mut self, depth: usize) {
+        // Later vectors cannot change the decided columns
+        let lower_bound = self.current.count_common_ones(&self.decided[depth]);
+        if lower_bound >= self.best.count_ones() {
+            return;
+        }
+        let Some(vector) = self.null_basis.get(depth) else {
+            // Everything is decided so the bound is the number of ones
+            self.best = self.current.clone();
+            return;
+        };
+        self.run(depth + 1);
+        self.current ^= vector;
+        self.run(depth + 1);
+        self.current ^= vector;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+
+    /// Bit vector with the given bits set
+    fn bit_vector(bits: &[bool]) -> BitVector {
+        let indices: Vec<usize> = (0..bits.len()).filter(|&i| bits[i]).collect();
+        BitVector::from_indices(bits.len(), &indices)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(500))]
+
+        #[test]
+        fn test_matches_brute_force(
+            (columns, target) in (1..=6usize, 0..8usize).prop_flat_map(|(num_rows, num_columns)| {
+                (
+                    vec(vec(any::<bool>(), num_rows), num_columns),
+                    vec(any::<bool>(), num_rows),
+                )
+            })
+        ) {
+            let num_rows = target.len();
+            let columns: Vec<BitVector> = columns.iter().map(|bits| bit_vector(bits)).collect();
+            let target = bit_vector(&target);
+
+            let mut expected = None;
+            for pressed in 0..1u32 << columns.len() {
+                let mut sum = BitVector::new(num_rows);
+                for (column, values) in columns.iter().enumerate() {
+                    if pressed >> column & 1 == 1 {
+                        sum ^= values;
+                    }
+                }
+                if sum == target {
+                    let weight = pressed.count_ones();
+                    expected = Some(expected.map_or(weight, |best: u32| best.min(weight)));
+                }
+            }
+
+            // Both with every combination and with branch and bound
+            for max_exhaustive in [EXHAUSTIVE_FREE_VARIABLES, 0] {
+                let actual = solve(&columns, &target, max_exhaustive);
+                prop_assert_eq!(actual.map(|x| x.count_ones()), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide() -> miette::Result<()> {
+        // Each button toggles a neighbouring pair of lights
+        let len = 100;
+        let columns: Vec<BitVector> = (0..len - 1)
+            .map(|i| BitVector::from_indices(len, &[i, i + 1]))
+            .collect();
+        let target = BitVector::from_indices(len, &[3, 90]);
+        let solution = min_weight_solution(&columns, &target).expect("solvable");
+        assert_eq!(
+            solution.ones().collect::<Vec<_>>(),
+            (3..90).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_many_free_variables() {
+        // Every button toggles the only light so all but one are free
+        let columns = vec![BitVector::from_indices(1, &[0]); EXHAUSTIVE_FREE_VARIABLES + 40];
+        let target = BitVector::from_indices(1, &[0]);
+        let solution = min_weight_solution(&columns, &target).expect("solvable");
+        assert_eq!(solution.count_ones(), 1);
+
+        // Neighbouring pairs and single lights, far too many combinations
+        // to try them all
+        let len = 40;
+        let columns: Vec<BitVector> = (0..len - 1)
+            .map(|i| BitVector::from_indices(len, &[i, i + 1]))
+            .chain((0..len).map(|i| BitVector::from_indices(len, &[i])))
+            .collect();
+        let target = BitVector::from_indices(len, &[3, 4, 5, 30]);
+        let solution = min_weight_solution(&columns, &target).expect("solvable");
+        // A pair and two singles since no two pairs can light 30
+        assert_eq!(solution.count_ones(), 3);
+    }
+}
@@ -0,0 +1,319 @@
+/// Finds non-negative integers `x` with the smallest total such that for
+/// every row `i`, adding `x[j]` once for each time `i` appears in
+/// `columns[j]` gives `target[i]` (`None` if there is no solution).
+///
+/// Gaussian elimination (kept in integers) writes every pivot variable in
+/// terms of the free variables. Since every coefficient is non-negative each
+/// variable is bounded by the targets of the rows it adds to, so the free
+/// variables can be searched with branch and bound. At each step the range
+/// each pivot variable could still take is used to prune assignments that
+/// cannot be feasible or cannot beat the best total found so far.
+pub fn min_total_solution(columns: &[Vec<usize>], target: &[u64]) -> Option<Vec<u64>> {
+    let num_rows = target.len();
+    let num_columns = columns.len();
+
+    // Each row is an equation with the right hand side in the last entry
+    let mut rows = vec![vec![0i128; num_columns + 1]; num_rows];
+    for (column, row_indices) in columns.iter().enumerate() {
+        for &row in row_indices {
+            rows[row][column] += 1;
+        }
+    }
+    for (row, &value) in rows.iter_mut().zip(target) {
+        row[num_columns] = value as i128;
+    }
+
+    let upper_bounds: Vec<i128> = (0..num_columns)
+        .map(|column| {
+            rows.iter()
+                .filter(|row| row[column] > 0)
+                .map(|row| row[num_columns] / row[column])
+                .min()
+                // Adds to nothing so pressing it never helps
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // Reduce to reduced row echelon form without leaving the integers
+    let mut pivot_columns = vec![];
+    for column in 0..num_columns {
+        let rank = pivot_columns.len();
+        let Some(pivot) = (rank..num_rows).find(|&row| rows[row][column] != 0) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        if rows[rank][column] < 0 {
+            rows[rank].iter_mut().for_each(|value| *value = -*value);
+        }
+        let pivot_row = rows[rank].clone();
+        for (row_index, row) in rows.iter_mut().enumerate() {
+            let factor = row[column];
+            if row_index == rank || factor == 0 {
+                continue;
+            }
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row.iter()) {
+                *value = *value * pivot_row[column] - pivot_value * factor;
+            }
+            let divisor = row.iter().fold(0, |acc, &value| gcd(acc, value));
+            if divisor > 1 {
+                row.iter_mut().for_each(|value| *value /= divisor);
+            }
+        }
+        pivot_columns.push(column);
+    }
+    if rows[pivot_columns.len()..]
+        .iter()
+        .any(|row| row[num_columns] != 0)
+    {
+        // 0 = non zero so no solution
+        return None;
+    }
+
+    let mut is_pivot = vec![false; num_columns];
+    for &column in pivot_columns.iter() {
+        is_pivot[column] = true;
+    }
+    // Fewest choices first so pruning happens as early as possible
+    let mut free_columns: Vec<usize> = (0..num_columns).filter(|&c| !is_pivot[c]).collect();
+    free_columns.sort_by_key(|&column| upper_bounds[column]);
+    let mut search = Search {
+        rows: &rows[..pivot_columns.len()],
+        pivot_columns: &pivot_columns,
+        free_columns,
+        upper_bounds: &upper_bounds,
+        values: vec![0; num_columns],
+        best: None,
+    };
+    search.run(0);
+    search.best.map(|(_, values)| values)
+}
+
+struct Search<'a> {
+    /// Non zero rows of the reduced matrix (row `i` has its pivot at
+    /// `pivot_columns[i]`)
+    rows: &'a [Vec<i128>],
+    pivot_columns: &'a [usize],
+    free_columns: Vec<usize>,
+    upper_bounds: &'a [i128],
+    /// Values of the free variables assigned so far
+    values: Vec<i128>,
+    best: Option<(i128, Vec<u64>)>,
+}
+
+impl Search<'_> {
+    /// Tries every value of the free variable at `depth` (and the ones after
+    /// it) that could still improve on the best solution
+    fn run(&mut self, depth: usize) {
+        let Some(lower_bound) = self.lower_bound(depth) else {
+            return;
+        };
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|(best, _)| lower_bound >= *best)
+        {
+            return;
+        }
+        let Some(&column) = self.free_columns.get(depth) else {
+            self.record_solution();
+            return;
+        };
+        let (lowest, highest) = self.value_range(depth);
+        for value in lowest..=highest {
+            self.values[column] = value;
+            self.run(depth + 1);
+        }
+        self.values[column] = 0;
+    }
+
+    /// Smallest total possible with the free variables before `depth` fixed
+    /// (`None` if no assignment of the rest can be feasible)
+    fn lower_bound(&self, depth: usize) -> Option<i128> {
+        let mut result: i128 = self.free_columns[..depth]
+            .iter()
+            .map(|&column| self.values[column])
+            .sum();
+        for (row, &pivot_column) in self.rows.iter().zip(self.pivot_columns) {
+            let (min, max) = self.remaining_range(row, depth, None);
+            let pivot = row[pivot_column];
+            let lowest = div_ceil(min, pivot).max(0);
+            let highest = max.div_euclid(pivot).min(self.upper_bounds[pivot_column]);
+            if lowest > highest {
+                return None;
+            }
+            result += lowest;
+        }
+        Some(result)
+    }
+
+    /// Values of the free variable at `depth` that keep every pivot variable
+    /// in range for some assignment of the later free variables
+    fn value_range(&self, depth: usize) -> (i128, i128) {
+        let column = self.free_columns[depth];
+        let mut lowest = 0;
+        let mut highest = self.upper_bounds[column];
+        for (row, &pivot_column) in self.rows.iter().zip(self.pivot_columns) {
+            let coefficient = row[column];
+            if coefficient == 0 {
+                continue;
+            }
+            // 0 <= rest - coefficient * x <= pivot * upper bound of pivot
+            let (min, max) = self.remaining_range(row, depth, Some(column));
+            let (low, high) = (
+                min - row[pivot_column] * self.upper_bounds[pivot_column],
+                max,
+            );
+            let (low, high) = if coefficient > 0 {
+                (low, high)
+            } else {
+                (-high, -low)
+            };
+            lowest = lowest.max(div_ceil(low, coefficient.abs()));
+            highest = highest.min(high.div_euclid(coefficient.abs()));
+        }
+        (lowest, highest)
+    }
+
+    /// Range of `pivot * x` for the pivot variable `x` of `row` given the
+    /// free variables before `depth` (leaving out `skip`)
+    fn remaining_range(&self, row: &[i128], depth: usize, skip: Option<usize>) -> (i128, i128) {
+        let num_columns = self.values.len();
+        // pivot * x = rhs - sum(row[free] * x[free])
+        let mut min = row[num_columns];
+        let mut max = row[num_columns];
+        for &column in self.free_columns[..depth].iter() {
+            min -= row[column] * self.values[column];
+            max -= row[column] * self.values[column];
+        }
+        for &column in self.free_columns[depth..].iter() {
+            if Some(column) == skip {
+                continue;
+            }
+            let extreme = row[column] * self.upper_bounds[column];
+            min -= extreme.max(0);
+            max -= extreme.min(0);
+        }
+        (min, max)
+    }
+
+    /// Every free variable is assigned so the pivot variables are fixed
+    fn record_solution(&mut self) {
+        let num_columns = self.values.len();
+        let mut values = self.values.clone();
+        for (row, &pivot_column) in self.rows.iter().zip(self.pivot_columns) {
+            let remainder: i128 = row[num_columns]
+                - self
+                    .free_columns
+                    .iter()
+                    .map(|&column| row[column] * self.values[column])
+                    .sum::<i128>();
+            if remainder % row[pivot_column] != 0 {
+                return;
+            }
+            values[pivot_column] = remainder / row[pivot_column];
+        }
+        // Range was checked by the lower bound so only the total is left
+        let total = values.iter().sum();
+        if self.best.as_ref().is_none_or(|(best, _)| total < *best) {
+            self.best = Some((
+                total,
+                values.into_iter().map(|value| value as u64).collect(),
+            ));
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Rounds towards positive infinity (`divisor` must be positive)
+fn div_ceil(value: i128, divisor: i128) -> i128 {
+    -(-value).div_euclid(divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+
+    fn is_solution(columns: &[Vec<usize>], target: &[u64], values: &[u64]) -> bool {
+        let mut totals = vec![0; target.len()];
+        for (column, &value) in columns.iter().zip(values) {
+            for &row in column {
+                totals[row] += value;
+            }
+        }
+        totals == target
+    }
+
+    /// Tries every combination of values up to the target
+    fn min_total_brute_force(columns: &[Vec<usize>], target: &[u64]) -> Option<u64> {
+        let limit = target.iter().copied().max().unwrap_or(0) + 1;
+        let mut values = vec![0; columns.len()];
+        let mut best = None;
+        loop {
+            if is_solution(columns, target, &values) {
+                let total: u64 = values.iter().sum();
+                best = Some(best.map_or(total, |best: u64| best.min(total)));
+            }
+            // Next combination (counting in base `limit`)
+            let Some(position) = values.iter().position(|&value| value + 1 < limit) else {
+                return best;
+            };
+            values[position] += 1;
+            values[..position].iter_mut().for_each(|value| *value = 0);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(300))]
+
+        #[test]
+        fn test_matches_brute_force_on_random_machines(
+            (buttons, presses, off_by_one) in (1..=4usize, 1..=4usize)
+                .prop_flat_map(|(num_rows, num_columns)| {
+                    (
+                        vec(vec(any::<bool>(), num_rows), num_columns),
+                        vec(0..3u64, num_columns),
+                        prop::bool::weighted(0.25),
+                    )
+                })
+        ) {
+            let columns: Vec<Vec<usize>> = buttons
+                .iter()
+                .map(|rows| (0..rows.len()).filter(|&row| rows[row]).collect())
+                .collect();
+            // Mostly reachable targets by pressing random amounts
+            let mut target = vec![0; buttons[0].len()];
+            for (column, &value) in columns.iter().zip(&presses) {
+                for &row in column {
+                    target[row] += value;
+                }
+            }
+            if off_by_one {
+                target[0] += 1;
+            }
+
+            let actual = min_total_solution(&columns, &target);
+            if let Some(values) = &actual {
+                prop_assert!(is_solution(&columns, &target, values));
+            }
+            prop_assert_eq!(
+                actual.map(|values| values.iter().sum::<u64>()),
+                min_total_brute_force(&columns, &target)
+            );
+        }
+    }
+
+    #[test]
+    fn test_needs_integer_solution() {
+        // Without the last button each of the others would need 1.5 presses
+        let columns = vec![vec![0, 1], vec![1, 2], vec![0, 2], vec![0, 1, 2]];
+        let target = [3, 3, 3];
+        let values = min_total_solution(&columns, &target).expect("solvable");
+        assert_eq!(values.iter().sum::<u64>(), 3);
+        assert!(is_solution(&columns, &target, &values));
+    }
+}
@@ -1,2 +1,8 @@
+pub mod explanation;
+pub mod gf2;
+pub mod ilp;
+pub mod machine;
 pub mod part1;
 pub mod part2;
+#[cfg(feature = "z3")]
+pub mod z3_solver;
@@ -0,0 +1,222 @@
+use std::ops::Range;
+
+use miette::{Context, LabeledSpan, Report, miette};
+
+/// One line of the manual, for example
+/// `[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    /// Which lights should be on (`#`)
+    pub lights: Vec<bool>,
+    /// Which lights (and joltage counters) each button changes
+    pub buttons: Vec<Vec<usize>>,
+    /// Joltage required for each counter
+    pub joltage: Vec<u64>,
+}
+
+pub fn parse_machines(input: &str) -> miette::Result<Vec<Machine>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Machine::parse(line).wrap_err_with(|| format!("invalid machine on line {}", i + 1))
+        })
+        .collect()
+}
+
+impl Machine {
+    /// Parses a single line and checks every button only refers to lights
+    /// that exist. Errors point at the part of the line that is wrong.
+    pub fn parse(line: &str) -> miette::Result<Self> {
+        let tokens = tokens(line);
+        let Some((&(lights_start, lights_token), rest)) = tokens.split_first() else {
+            return Err(error(line, 0..line.len(), "empty", "expected a machine"));
+        };
+        let Some((&(joltage_start, joltage_token), buttons_tokens)) = rest.split_last() else {
+            return Err(error(
+                line,
+                line.len()..line.len(),
+                "expected joltage like {3,5,4,7}",
+                "missing joltage",
+            ));
+        };
+
+        let diagram = enclosed(line, lights_start, lights_token, '[', ']', "light diagram")?;
+        let mut lights = vec![];
+        for (offset, c) in diagram.char_indices() {
+            lights.push(match c {
+                '#' => true,
+                '.' => false,
+                _ => {
+                    let start = lights_start + 1 + offset;
+                    return Err(error(
+                        line,
+                        start..start + c.len_utf8(),
+                        "expected . or #",
+                        "unexpected character in light diagram",
+                    ));
+                }
+            });
+        }
+
+        let joltage_list = enclosed(line, joltage_start, joltage_token, '{', '}', "joltage")?;
+        let joltage: Vec<u64> = numbers(line, joltage_start + 1, joltage_list)?
+            .into_iter()
+            .map(|(value, _)| value)
+            .collect();
+        if joltage.len() != lights.len() {
+            return Err(error(
+                line,
+                joltage_start..joltage_start + joltage_token.len(),
+                &format!("expected {} values (one per light)", lights.len()),
+                "joltage count does not match number of lights",
+            ));
+        }
+
+        let mut buttons = vec![];
+        for &(start, token) in buttons_tokens {
+            let list = enclosed(line, start, token, '(', ')', "button")?;
+            let mut button: Vec<usize> = vec![];
+            for (index, span) in numbers(line, start + 1, list)? {
+                let index = index as usize;
+                if index >= lights.len() {
+                    return Err(error(
+                        line,
+                        span,
+                        &format!("there are only {} lights", lights.len()),
+                        "button index out of range",
+                    ));
+                }
+                if button.contains(&index) {
+                    return Err(error(
+                        line,
+                        span,
+                        "already listed",
+                        "duplicate index in button",
+                    ));
+                }
+                button.push(index);
+            }
+            buttons.push(button);
+        }
+
+        Ok(Self {
+            lights,
+            buttons,
+            joltage,
+        })
+    }
+}
+
+/// Whitespace separated parts of `line` with the offset they start at
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut result = vec![];
+    let mut start = None;
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(token_start)) => {
+                result.push((token_start, &line[token_start..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Contents of `token` after checking it is wrapped in `open` and `close`
+fn enclosed<'a>(
+    line: &str,
+    start: usize,
+    token: &'a str,
+    open: char,
+    close: char,
+    name: &str,
+) -> miette::Result<&'a str> {
+    token
+        .strip_prefix(open)
+        .and_then(|token| token.strip_suffix(close))
+        .ok_or_else(|| {
+            error(
+                line,
+                start..start + token.len(),
+                &format!("expected {name} in {open}{close}"),
+                &format!("invalid {name}"),
+            )
+        })
+}
+
+/// Comma separated numbers in `list` (which starts at `start` in `line`) with
+/// their spans
+fn numbers(line: &str, start: usize, list: &str) -> miette::Result<Vec<(u64, Range<usize>)>> {
+    let mut result = vec![];
+    let mut offset = start;
+    for item in list.split(',') {
+        let span = offset..offset + item.len();
+        let value = item.parse().map_err(|_| {
+            error(
+                line,
+                if item.is_empty() {
+                    offset..offset + 1
+                } else {
+                    span.clone()
+                },
+                "expected a number",
+                "invalid number",
+            )
+        })?;
+        result.push((value, span));
+        offset += item.len() + 1;
+    }
+    Ok(result)
+}
+
+fn error(line: &str, span: Range<usize>, label: &str, message: &str) -> Report {
+    miette!(labels = vec![LabeledSpan::at(span, label)], "{message}")
+        .with_source_code(line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start of the first label of the error from parsing `line`
+    fn error_offset(line: &str) -> usize {
+        let report = Machine::parse(line).expect_err("line is invalid");
+        report
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .expect("errors are labelled")
+            .offset()
+    }
+
+    #[test]
+    fn test_parse() -> miette::Result<()> {
+        let machine = Machine::parse("[.##.] (3) (1,3) (2) {3,5,4,7}")?;
+        assert_eq!(machine.lights, [false, true, true, false]);
+        assert_eq!(machine.buttons, [vec![3], vec![1, 3], vec![2]]);
+        assert_eq!(machine.joltage, [3, 5, 4, 7]);
+
+        // Wider than 16 lights
+        let line = format!("[{}#] (0,19) (18) {{{}1}}", ".".repeat(19), "0,".repeat(19));
+        let machine = Machine::parse(&line)?;
+        assert_eq!(machine.buttons[0], [0, 19]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_point_at_problem() {
+        assert_eq!(error_offset("[.#] (0) (2) {1,1}"), 10);
+        assert_eq!(error_offset("[.#] (0,0) {1,1}"), 8);
+        assert_eq!(error_offset("[.x] (0) {1,1}"), 2);
+        assert_eq!(error_offset("[.#] (0,a) {1,1}"), 8);
+        assert_eq!(error_offset("[.#] (0,) {1,1}"), 8);
+        assert_eq!(error_offset("[.#] 0 {1,1}"), 5);
+        assert_eq!(error_offset("[.#] (0) {1}"), 9);
+        assert_eq!(error_offset("[.#] (0)"), 5);
+        assert_eq!(error_offset("[.#]"), 4);
+        assert_eq!(error_offset(""), 0);
+        assert!(parse_machines("[.#] (1) {0,1}\n[.#] (5) {0,1}\n").is_err());
+    }
+}
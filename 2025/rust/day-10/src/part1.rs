@@ -1,131 +1,91 @@
-use std::{collections::VecDeque, fmt::Debug};
+use miette::{Context, bail};
 
-use miette::Context;
+use crate::{
+    explanation::Explanation,
+    gf2::{BitVector, min_weight_solution},
+    machine::{Machine, parse_machines},
+};
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let mut result = 0;
-    let machines = parse_machines(input);
-    for machine in machines.iter() {
-        result += min_presses_for_machine(machine);
-    }
+    let result: u64 = explain(input)?.iter().map(Explanation::total).sum();
     Ok(result.to_string())
 }
 
-fn min_presses_for_machine(machine: &Machine) -> u32 {
-    let mut queue = VecDeque::new();
-    for i in 0..machine.buttons.len() {
-        queue.push_back(PressTracker {
-            next_button: i,
-            ..Default::default()
-        });
-    }
-    loop {
-        let mut tracker = queue
-            .pop_front()
-            .wrap_err_with(|| {
-                format!(
-                    "Error: no more buttons to press but desired pattern not found. {machine:?}"
-                )
-            })
-            .unwrap();
-        // eprintln!("{tracker:?}");
-        tracker.press_count += 1;
-        tracker.state ^= machine.buttons[tracker.next_button];
+/// The buttons to press for each machine after checking they give the
+/// desired pattern
+pub fn explain(input: &str) -> miette::Result<Vec<Explanation>> {
+    let machines = parse_machines(input)?;
+    let mut result = vec![];
+    for (i, machine) in machines.iter().enumerate() {
+        let machine = LightsMachine::from(machine);
+        let presses = min_presses_for_machine(&machine)
+            .with_context(|| format!("failed to process machine: {machine:?}"))?;
 
-        if tracker.state == machine.target {
-            return tracker.press_count as _;
-        }
-        tracker.pressed += 1 << tracker.next_button; // Mark button as pressed
-        // eprintln!("{tracker:?}\n");
-        for i in 0..machine.buttons.len() {
-            let button_bit_pos = 1 << i;
-            if (button_bit_pos & tracker.pressed) == 0 {
-                // Button not pressed enqueue to be pressed
-                queue.push_back(PressTracker {
-                    next_button: i,
-                    ..tracker
-                });
+        let mut lights = BitVector::new(machine.target.len());
+        for (button, &count) in machine.buttons.iter().zip(&presses) {
+            if count % 2 == 1 {
+                lights ^= button;
             }
         }
-    }
-}
+        let outcome = format_lights(&lights);
+        if lights != machine.target {
+            bail!(
+                "presses {presses:?} give {outcome} instead of {} for machine on line {}",
+                format_lights(&machine.target),
+                i + 1
+            );
+        }
 
-#[derive(Default, Clone, Copy)]
-struct PressTracker {
-    state: u16,
-    press_count: u8,
-    pressed: u16,
-    next_button: usize,
+        result.push(Explanation {
+            line: i + 1,
+            buttons: machine.buttons.iter().map(|b| b.ones().collect()).collect(),
+            presses,
+            outcome,
+        });
+    }
+    Ok(result)
 }
 
-struct Machine {
-    /// Bit map of target lights
-    target: u16,
-
-    /// Bit map of which lights each button toggles
-    buttons: Vec<u16>,
-    joltage: Vec<u16>,
+/// Pressing a button twice cancels out so each button is pressed at most once
+/// and the buttons to press are a solution to a linear system over GF(2) (one
+/// equation per light)
+fn min_presses_for_machine(machine: &LightsMachine) -> miette::Result<Vec<u64>> {
+    match min_weight_solution(&machine.buttons, &machine.target) {
+        Some(pressed) => Ok((0..pressed.len()).map(|i| pressed.get(i) as u64).collect()),
+        None => bail!("no combination of buttons gives the desired pattern"),
+    }
 }
 
-fn parse_machines(input: &str) -> Vec<Machine> {
-    input.lines().map(parse_machine).collect()
+fn format_lights(lights: &BitVector) -> String {
+    let lights: String = (0..lights.len())
+        .map(|i| if lights.get(i) { '#' } else { '.' })
+        .collect();
+    format!("[{lights}]")
 }
 
-fn parse_machine(line: &str) -> Machine {
-    let (indicators, rest) = line.split_once("]").unwrap();
-    let target = indicators[1..]
-        .char_indices()
-        .fold(0, |acc, (i, c)| if c == '#' { acc + (1 << i) } else { acc });
-    let mut buttons = vec![];
-    let mut joltage = vec![];
-    for part in rest.split(")") {
-        let part = &part[2..]; // Remove space and opening bracket
-        if *part.as_bytes().last().unwrap() == b'}' {
-            // Process joltage info
-            for str_value in part[..part.len() - 1].split(",") {
-                joltage.push(str_value.parse().unwrap());
-            }
-        } else {
-            // Extract info for button
-            buttons.push(
-                part.split(",")
-                    .map(|x| x.parse().unwrap())
-                    .fold(0, |acc, x: usize| acc + (1 << x)),
-            );
-        }
-    }
+/// The parts of a machine that matter for the lights
+#[derive(Debug)]
+struct LightsMachine {
+    /// Which lights should be on
+    target: BitVector,
 
-    Machine {
-        target,
-        buttons,
-        joltage,
-    }
+    /// Which lights each button toggles
+    buttons: Vec<BitVector>,
 }
 
-impl Debug for Machine {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buttons_iter = self.buttons.iter();
-        let mut buttons = format!("{:b}", buttons_iter.next().unwrap());
-        for button in buttons_iter {
-            buttons.push_str(&format!(", {:b}", button));
+impl From<&Machine> for LightsMachine {
+    fn from(machine: &Machine) -> Self {
+        let num_lights = machine.lights.len();
+        let lights: Vec<usize> = (0..num_lights).filter(|&i| machine.lights[i]).collect();
+        Self {
+            target: BitVector::from_indices(num_lights, &lights),
+            buttons: machine
+                .buttons
+                .iter()
+                .map(|button| BitVector::from_indices(num_lights, button))
+                .collect(),
         }
-        f.debug_struct("Machine")
-            .field("target", &format!("{:b}", self.target))
-            .field("buttons", &buttons)
-            .field("joltage", &self.joltage)
-            .finish()
-    }
-}
-
-impl Debug for PressTracker {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("PressTracker")
-            .field("state", &format!("{:b}", self.state))
-            .field("press_count", &self.press_count)
-            .field("pressed", &self.pressed)
-            .field("next_button", &self.next_button)
-            .finish()
     }
 }
 
@@ -142,4 +102,27 @@ mod tests {
         assert_eq!(process(input)?, "7");
         Ok(())
     }
+
+    #[test]
+    fn test_explain() -> miette::Result<()> {
+        let explanations = explain("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n")?;
+        assert_eq!(explanations.len(), 1);
+        assert_eq!(explanations[0].total(), 2);
+        let text = explanations[0].to_string();
+        assert!(text.starts_with("line 1: 2 presses give [.##.]"), "{text}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_more_than_16_lights_and_buttons() -> miette::Result<()> {
+        // Button i toggles lights i and i + 1
+        let buttons: Vec<String> = (0..19).map(|i| format!("({i},{})", i + 1)).collect();
+        let input = format!(
+            "[.#.................#] {} {{{}0}}\n",
+            buttons.join(" "),
+            "0,".repeat(19)
+        );
+        assert_eq!(process(&input)?, "18");
+        Ok(())
+    }
 }
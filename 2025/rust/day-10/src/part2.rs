@@ -1,114 +1,68 @@
-use std::fmt::Debug;
-
 use miette::{Context, bail};
-use z3::{Optimize, ast::Int};
+
+use crate::{
+    explanation::Explanation,
+    ilp::min_total_solution,
+    machine::{Machine, parse_machines},
+};
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let mut result = 0;
-    let machines = parse_machines(input);
-    for machine in machines.iter() {
-        result += min_presses_for_machine(machine)
-            .with_context(|| format!("failed to process machine: {machine:?}"))?;
-    }
+    let result: u64 = explain(input)?.iter().map(Explanation::total).sum();
     Ok(result.to_string())
 }
 
-fn min_presses_for_machine(machine: &Machine) -> miette::Result<u32> {
-    // Create mapping from counter to the buttons that increment it
-    let mut counter_buttons = vec![vec![]; machine.joltage.len()];
-    for (i, button) in machine.buttons.iter().enumerate() {
-        for &controller in button {
-            counter_buttons[controller].push(i);
-        }
-    }
-
-    // instantiate a Solver
-    let optimizer = Optimize::new();
-
-    // Create ints for the number of button presses
-    let buttons_ints: Vec<_> = (0..machine.buttons.len())
-        .map(|i| Int::new_const(i as i32))
-        .collect();
-
-    // encode the constraints of the problem as Bool-valued Asts
-    // and assert them in the optimizer
-
-    // Number of presses non-negative for all buttons
-    for button_int in buttons_ints.iter() {
-        optimizer.assert(&button_int.ge(0));
-    }
+/// The number of times to press each button for each machine after checking
+/// they give the desired joltage
+pub fn explain(input: &str) -> miette::Result<Vec<Explanation>> {
+    let machines = parse_machines(input)?;
+    let mut result = vec![];
+    for (i, machine) in machines.iter().enumerate() {
+        let presses = min_presses_for_machine(machine)
+            .with_context(|| format!("failed to process machine: {machine:?}"))?;
 
-    // Joltage correct after presses are done (for each joltage add up the buttons
-    // that increment that control)
-    for (i, &joltage) in machine.joltage.iter().enumerate() {
-        if let Some(&first) = counter_buttons[i].first() {
-            let mut sum = buttons_ints[first].clone();
-            for &button_index in counter_buttons[i].iter().skip(1) {
-                sum += buttons_ints[button_index].clone();
+        let mut joltage = vec![0u64; machine.joltage.len()];
+        for (button, &count) in machine.buttons.iter().zip(&presses) {
+            for &counter in button {
+                joltage[counter] += count;
             }
-            optimizer.assert(&sum.eq(joltage));
         }
-    }
-
-    // Minimize total number of button presses
-    let mut total_button_presses = Int::from_i64(0);
-    for button_int in buttons_ints.iter() {
-        total_button_presses += button_int;
-    }
-    optimizer.minimize(&total_button_presses);
-
-    let solve_output = optimizer.check(&[]);
-    if solve_output != z3::SatResult::Sat {
-        bail!("found no solution: {solve_output:?}");
-    }
-
-    let model = optimizer.get_model().wrap_err("failed to get model")?;
+        let outcome = format_joltage(&joltage);
+        if joltage != machine.joltage {
+            bail!(
+                "presses {presses:?} give {outcome} instead of {:?} for machine on line {}",
+                machine.joltage,
+                i + 1
+            );
+        }
 
-    let mut result = 0;
-    for button_int in buttons_ints.iter() {
-        result += model
-            .get_const_interp(button_int)
-            .wrap_err("failed to get interpretation of button press count")?
-            .as_i64()
-            .wrap_err("failed to convert to u64")?;
+        result.push(Explanation {
+            line: i + 1,
+            buttons: machine.buttons.clone(),
+            presses,
+            outcome,
+        });
     }
-
-    Ok(result as _)
+    Ok(result)
 }
 
-#[derive(Debug)]
-struct Machine {
-    buttons: Vec<Vec<usize>>,
-    joltage: Vec<u16>,
+fn min_presses_for_machine(machine: &Machine) -> miette::Result<Vec<u64>> {
+    let Some(presses) = min_total_solution(&machine.buttons, &machine.joltage) else {
+        bail!("found no solution");
+    };
+    Ok(presses)
 }
 
-fn parse_machines(input: &str) -> Vec<Machine> {
-    input.lines().map(parse_machine).collect()
-}
-
-fn parse_machine(line: &str) -> Machine {
-    let (_indicators, rest) = line.split_once("]").unwrap();
-    let mut buttons = vec![];
-    let mut joltage = vec![];
-    for part in rest.split(")") {
-        let part = &part[2..]; // Remove space and opening bracket
-        if *part.as_bytes().last().unwrap() == b'}' {
-            // Process joltage info
-            for str_value in part[..part.len() - 1].split(",") {
-                joltage.push(str_value.parse().unwrap());
-            }
-        } else {
-            // Extract info for button
-            buttons.push(part.split(",").map(|x| x.parse().unwrap()).collect());
-        }
-    }
-
-    Machine { buttons, joltage }
+fn format_joltage(joltage: &[u64]) -> String {
+    let values: Vec<String> = joltage.iter().map(|value| value.to_string()).collect();
+    format!("{{{}}}", values.join(","))
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "z3")]
+    use proptest::{collection::vec, prelude::*};
+
     use super::*;
 
     #[test]
@@ -120,4 +74,53 @@ mod tests {
         assert_eq!(process(input)?, "33");
         Ok(())
     }
+
+    #[test]
+    fn test_explain() -> miette::Result<()> {
+        let explanations = explain("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n")?;
+        assert_eq!(explanations[0].total(), 10);
+        assert_eq!(explanations[0].outcome, "{3,5,4,7}");
+        Ok(())
+    }
+
+    #[cfg(feature = "z3")]
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+
+        #[test]
+        fn test_matches_z3_on_random_machines(
+            (buttons, presses) in (1..=8usize, 1..=10usize)
+                .prop_flat_map(|(num_counters, num_buttons)| {
+                    (
+                        vec(vec(prop::bool::weighted(1.0 / 3.0), num_counters), num_buttons),
+                        vec(0..20u64, num_buttons),
+                    )
+                })
+        ) {
+            let num_counters = buttons[0].len();
+            let buttons: Vec<Vec<usize>> = buttons
+                .iter()
+                .map(|counters| (0..num_counters).filter(|&i| counters[i]).collect())
+                .collect();
+            let mut joltage = vec![0; num_counters];
+            for (button, &presses) in buttons.iter().zip(&presses) {
+                for &counter in button {
+                    joltage[counter] += presses;
+                }
+            }
+            let machine = Machine {
+                lights: vec![false; num_counters],
+                buttons,
+                joltage,
+            };
+            let joltage: Vec<u16> = machine.joltage.iter().map(|&value| value as u16).collect();
+            let fail = |error: miette::Report| TestCaseError::fail(error.to_string());
+            prop_assert_eq!(
+                min_presses_for_machine(&machine).map_err(fail)?.iter().sum::<u64>() as u32,
+                crate::z3_solver::min_presses(&machine.buttons, &joltage).map_err(fail)?,
+                "{:?}",
+                machine
+            );
+        }
+    }
 }
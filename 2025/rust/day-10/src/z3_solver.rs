@@ -0,0 +1,67 @@
+use miette::{Context, bail};
+use z3::{Optimize, ast::Int};
+
+/// Minimum total presses to reach `joltage` found with z3 (used to cross check
+/// the native solver)
+pub fn min_presses(buttons: &[Vec<usize>], joltage: &[u16]) -> miette::Result<u32> {
+    // Create mapping from counter to the buttons that increment it
+    let mut counter_buttons = vec![vec![]; joltage.len()];
+    for (i, button) in buttons.iter().enumerate() {
+        for &controller in button {
+            counter_buttons[controller].push(i);
+        }
+    }
+
+    // instantiate a Solver
+    let optimizer = Optimize::new();
+
+    // Create ints for the number of button presses
+    let buttons_ints: Vec<_> = (0..buttons.len())
+        .map(|i| Int::new_const(i as i32))
+        .collect();
+
+    // encode the constraints of the problem as Bool-valued Asts
+    // and assert them in the optimizer
+
+    // Number of presses non-negative for all buttons
+    for button_int in buttons_ints.iter() {
+        optimizer.assert(&button_int.ge(0));
+    }
+
+    // Joltage correct after presses are done (for each joltage add up the buttons
+    // that increment that control)
+    for (i, &joltage) in joltage.iter().enumerate() {
+        if let Some(&first) = counter_buttons[i].first() {
+            let mut sum = buttons_ints[first].clone();
+            for &button_index in counter_buttons[i].iter().skip(1) {
+                sum += buttons_ints[button_index].clone();
+            }
+            optimizer.assert(&sum.eq(joltage));
+        }
+    }
+
+    // Minimize total number of button presses
+    let mut total_button_presses = Int::from_i64(0);
+    for button_int in buttons_ints.iter() {
+        total_button_presses += button_int;
+    }
+    optimizer.minimize(&total_button_presses);
+
+    let solve_output = optimizer.check(&[]);
+    if solve_output != z3::SatResult::Sat {
+        bail!("found no solution: {solve_output:?}");
+    }
+
+    let model = optimizer.get_model().wrap_err("failed to get model")?;
+
+    let mut result = 0;
+    for button_int in buttons_ints.iter() {
+        result += model
+            .get_const_interp(button_int)
+            .wrap_err("failed to get interpretation of button press count")?
+            .as_i64()
+            .wrap_err("failed to convert to u64")?;
+    }
+
+    Ok(result as _)
+}
@@ -1,12 +1,50 @@
-use day_11::part2::process;
-use miette::Context;
+use day_11::{
+    dot::to_dot,
+    graph::parse_graph,
+    part2::{END, START, WAYPOINTS, process},
+    paths::enumerate_paths,
+};
+use miette::{Context, IntoDiagnostic, bail};
+
+const USAGE: &str = "usage: part2 [--dot|--paths [LIMIT]]
+
+  --dot            print the devices as Graphviz DOT with every edge labelled
+                   with the number of paths from svr to out (through fft and dac)
+                   that use it
+  --paths [LIMIT]  print the paths from svr to out (through fft and dac) one
+                   per line (at most LIMIT of them if given)";
 
 #[tracing::instrument]
 fn main() -> miette::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input2.txt");
-    let result = process(file).context("process part 2")?;
-    println!("{}", result);
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        None => {
+            let result = process(file).context("process part 2")?;
+            println!("{}", result);
+        }
+        Some("--dot") => {
+            let graph = parse_graph(file)?;
+            print!("{}", to_dot(&graph, START, END, &WAYPOINTS)?);
+        }
+        Some("--paths") => {
+            let limit = args
+                .next()
+                .map(|value| {
+                    value
+                        .parse::<usize>()
+                        .into_diagnostic()
+                        .wrap_err_with(|| format!("invalid limit {value:?}\n{USAGE}"))
+                })
+                .transpose()?;
+            let graph = parse_graph(file)?;
+            for path in enumerate_paths(&graph, START, END, &WAYPOINTS, limit)? {
+                println!("{}", path.join(" -> "));
+            }
+        }
+        Some(other) => bail!("unexpected argument {other:?}\n{USAGE}"),
+    }
     Ok(())
 }
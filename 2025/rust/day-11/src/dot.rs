@@ -0,0 +1,101 @@
+use std::fmt::Write;
+
+use crate::{graph::Graph, part1, part2, path_count::count_paths_per_edge};
+
+/// Graphviz DOT of the devices with `start`, `end` and the `waypoints`
+/// highlighted, along with the start of each part if it is in the graph.
+/// Every edge is labelled with how many paths from `start` to `end` (through
+/// all the waypoints) use it and drawn thicker the more there are, edges no
+/// path uses are greyed out.
+pub fn to_dot(graph: &Graph, start: &str, end: &str, waypoints: &[&str]) -> miette::Result<String> {
+    let edges = count_paths_per_edge(graph, start, end, waypoints)?;
+    let most_paths = edges.iter().map(|&(_, _, paths)| paths).max().unwrap_or(0);
+
+    let mut result = String::from("digraph devices {\n  node [shape=ellipse];\n");
+    let mut starts = vec![start];
+    for known in [part1::START, part2::START] {
+        if !starts.contains(&known) && graph.id(known).is_some() {
+            starts.push(known);
+        }
+    }
+    for start in starts {
+        writeln!(
+            result,
+            "  \"{start}\" [style=filled, fillcolor=palegreen, shape=box];"
+        )
+        .unwrap();
+    }
+    writeln!(
+        result,
+        "  \"{end}\" [style=filled, fillcolor=lightcoral, shape=box];"
+    )
+    .unwrap();
+    for waypoint in waypoints {
+        writeln!(result, "  \"{waypoint}\" [style=filled, fillcolor=gold];").unwrap();
+    }
+    for (from, to, paths) in edges {
+        let (from, to) = (graph.name(from), graph.name(to));
+        if paths == 0 {
+            writeln!(result, "  \"{from}\" -> \"{to}\" [color=grey];").unwrap();
+        } else {
+            // Scaled by the fraction of the busiest edge's paths
+            let width = 1.0 + 4.0 * paths as f64 / most_paths as f64;
+            writeln!(
+                result,
+                "  \"{from}\" -> \"{to}\" [label=\"{paths}\", penwidth={width:.1}];"
+            )
+            .unwrap();
+        }
+    }
+    result.push_str("}\n");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::parse_graph;
+
+    #[test]
+    fn test_to_dot() -> miette::Result<()> {
+        let graph = parse_graph("you: a b\na: out\nb: out c\nc: out\nx: a\n")?;
+        let dot = to_dot(&graph, "you", "out", &["b"])?;
+        assert_eq!(
+            dot,
+            r#"digraph devices {
+  node [shape=ellipse];
+  "you" [style=filled, fillcolor=palegreen, shape=box];
+  "out" [style=filled, fillcolor=lightcoral, shape=box];
+  "b" [style=filled, fillcolor=gold];
+  "you" -> "a" [color=grey];
+  "you" -> "b" [label="2", penwidth=5.0];
+  "a" -> "out" [color=grey];
+  "b" -> "out" [label="1", penwidth=3.0];
+  "b" -> "c" [label="1", penwidth=3.0];
+  "c" -> "out" [label="1", penwidth=3.0];
+  "x" -> "a" [color=grey];
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_highlights_both_starts() -> miette::Result<()> {
+        let graph = parse_graph("svr: you fft\nyou: dac\nfft: dac\ndac: out\n")?;
+        let highlighted = |dot: &str, name: &str| {
+            dot.contains(&format!(
+                "\"{name}\" [style=filled, fillcolor=palegreen, shape=box];"
+            ))
+        };
+        for dot in [
+            to_dot(&graph, part1::START, part1::END, &[])?,
+            to_dot(&graph, part2::START, part2::END, &part2::WAYPOINTS)?,
+        ] {
+            assert!(highlighted(&dot, "you"), "{dot}");
+            assert!(highlighted(&dot, "svr"), "{dot}");
+            assert_eq!(dot.matches("palegreen").count(), 2);
+        }
+        Ok(())
+    }
+}
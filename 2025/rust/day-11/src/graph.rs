@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use miette::{Context, bail};
+
+/// Dense ID of a device (index into [`Graph::names`])
+pub type NodeId = u32;
+
+/// Devices and their outputs with names interned to dense IDs (in order of
+/// first appearance) and the outputs (and inputs) stored in compressed sparse
+/// row form.
+#[derive(Debug, Default)]
+pub struct Graph<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, NodeId>,
+    /// Outputs of node `i` are `outputs[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    outputs: Vec<NodeId>,
+    /// Same as `offsets` and `outputs` with every edge reversed
+    input_offsets: Vec<usize>,
+    inputs: Vec<NodeId>,
+}
+
+/// Reads one line per device (`name: output output ...`). Devices that only
+/// appear as an output have no outputs of their own.
+///
+/// Fails if a device is listed twice. Cycles are allowed as long as they are
+/// not on a route that is counted (see [`Graph::route_order`]).
+pub fn parse_graph(input: &str) -> miette::Result<Graph<'_>> {
+    let mut result = Graph::default();
+    let mut lines: Vec<(NodeId, Vec<NodeId>)> = vec![];
+    let mut listed = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let (node, neighbours) = line
+            .split_once(":")
+            .wrap_err_with(|| format!("expected \"name: outputs\" on line {}", i + 1))?;
+        let node = result.intern(node);
+        if listed.get(node as usize).copied().unwrap_or(false) {
+            bail!(
+                "device {:?} is listed more than once (line {})",
+                result.name(node),
+                i + 1
+            );
+        }
+        let neighbours = neighbours
+            .split_whitespace()
+            .map(|name| result.intern(name))
+            .collect();
+        listed.resize(result.len(), false);
+        listed[node as usize] = true;
+        lines.push((node, neighbours));
+    }
+
+    let mut adjacency = vec![vec![]; result.len()];
+    let mut reverse_adjacency = vec![vec![]; result.len()];
+    for (node, neighbours) in lines {
+        for &neighbour in neighbours.iter() {
+            reverse_adjacency[neighbour as usize].push(node);
+        }
+        adjacency[node as usize] = neighbours;
+    }
+    (result.offsets, result.outputs) = compress(adjacency);
+    (result.input_offsets, result.inputs) = compress(reverse_adjacency);
+    Ok(result)
+}
+
+/// Offsets and concatenated lists for compressed sparse row form
+fn compress(lists: Vec<Vec<NodeId>>) -> (Vec<usize>, Vec<NodeId>) {
+    let mut offsets = vec![0];
+    let mut values = vec![];
+    for list in lists {
+        values.extend(list);
+        offsets.push(values.len());
+    }
+    (offsets, values)
+}
+
+impl<'a> Graph<'a> {
+    fn intern(&mut self, name: &'a str) -> NodeId {
+        *self.ids.entry(name).or_insert_with(|| {
+            self.names.push(name);
+            (self.names.len() - 1) as NodeId
+        })
+    }
+
+    /// Number of devices
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    #[must_use]
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    #[must_use]
+    pub fn name(&self, id: NodeId) -> &'a str {
+        self.names[id as usize]
+    }
+
+    /// Names of all devices indexed by ID
+    #[must_use]
+    pub fn names(&self) -> &[&'a str] {
+        &self.names
+    }
+
+    #[must_use]
+    pub fn outputs(&self, id: NodeId) -> &[NodeId] {
+        &self.outputs[self.offsets[id as usize]..self.offsets[id as usize + 1]]
+    }
+
+    /// Devices that have `id` as an output
+    #[must_use]
+    pub fn inputs(&self, id: NodeId) -> &[NodeId] {
+        &self.inputs[self.input_offsets[id as usize]..self.input_offsets[id as usize + 1]]
+    }
+
+    /// Nodes on some path from `start` to `end` (paths stop at `end`) in an
+    /// order where every node comes before all of its outputs.
+    ///
+    /// Fails if those nodes form a cycle (there would be infinitely many
+    /// paths). Cycles anywhere else in the graph do not matter.
+    pub fn route_order(&self, start: NodeId, end: NodeId) -> miette::Result<Vec<NodeId>> {
+        let from_start = self.search(start, Some(end), |node| self.outputs(node));
+        let to_end = self.search(end, None, |node| self.inputs(node));
+        let on_route: Vec<bool> = from_start
+            .iter()
+            .zip(to_end)
+            .map(|(&forward, backward)| forward && backward)
+            .collect();
+        // Edges followed by paths (nothing continues past the end)
+        let route_outputs = |node: NodeId| {
+            let outputs = if node == end {
+                &[][..]
+            } else {
+                self.outputs(node)
+            };
+            outputs
+                .iter()
+                .copied()
+                .filter(|&output| on_route[output as usize])
+        };
+
+        // Kahn's algorithm (leaves out any nodes on or after a cycle)
+        let mut inputs = vec![0; self.len()];
+        for node in (0..self.len() as NodeId).filter(|&node| on_route[node as usize]) {
+            for output in route_outputs(node) {
+                inputs[output as usize] += 1;
+            }
+        }
+        let mut result: Vec<NodeId> = (0..self.len() as NodeId)
+            .filter(|&node| on_route[node as usize] && inputs[node as usize] == 0)
+            .collect();
+        let mut next = 0;
+        while let Some(&node) = result.get(next) {
+            next += 1;
+            for output in route_outputs(node) {
+                inputs[output as usize] -= 1;
+                if inputs[output as usize] == 0 {
+                    result.push(output);
+                }
+            }
+        }
+        if result.len() < on_route.iter().filter(|&&on| on).count() {
+            let cycle = self
+                .find_cycle(&on_route, route_outputs)
+                .expect("nodes left over means a cycle");
+            bail!("devices form a cycle: {}", cycle.join(" -> "));
+        }
+        Ok(result)
+    }
+
+    /// Nodes that can be reached from `from` following `next` (without going
+    /// past `stop`)
+    fn search<'g>(
+        &'g self,
+        from: NodeId,
+        stop: Option<NodeId>,
+        next: impl Fn(NodeId) -> &'g [NodeId],
+    ) -> Vec<bool> {
+        let mut seen = vec![false; self.len()];
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut seen[node as usize], true) || Some(node) == stop {
+                continue;
+            }
+            stack.extend_from_slice(next(node));
+        }
+        seen
+    }
+
+    /// Nodes along a cycle among the `within` nodes following `outputs` (with
+    /// the first node repeated at the end) if there is one
+    fn find_cycle<I: Iterator<Item = NodeId>>(
+        &self,
+        within: &[bool],
+        outputs: impl Fn(NodeId) -> I,
+    ) -> Option<Vec<&'a str>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum State {
+            New,
+            OnPath,
+            Done,
+        }
+        let mut states = vec![State::New; self.len()];
+        for root in (0..self.len() as NodeId).filter(|&node| within[node as usize]) {
+            if states[root as usize] != State::New {
+                continue;
+            }
+            // Depth first with the path so far and the outputs of each node on
+            // it that are still to be followed
+            let mut path = vec![(root, outputs(root))];
+            states[root as usize] = State::OnPath;
+            while let Some((node, remaining)) = path.last_mut() {
+                let Some(neighbour) = remaining.next() else {
+                    states[*node as usize] = State::Done;
+                    path.pop();
+                    continue;
+                };
+                match states[neighbour as usize] {
+                    State::Done => {}
+                    State::OnPath => {
+                        let start = path
+                            .iter()
+                            .position(|(node, _)| *node == neighbour)
+                            .expect("node is on the path");
+                        let mut cycle: Vec<&'a str> = path[start..]
+                            .iter()
+                            .map(|(node, _)| self.name(*node))
+                            .collect();
+                        cycle.push(self.name(neighbour));
+                        return Some(cycle);
+                    }
+                    State::New => {
+                        states[neighbour as usize] = State::OnPath;
+                        path.push((neighbour, outputs(neighbour)));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Names of the nodes that cannot be reached from `start` (in name order)
+    #[must_use]
+    pub fn unreachable_from(&self, start: NodeId) -> Vec<&'a str> {
+        let seen = self.search(start, None, |node| self.outputs(node));
+        let mut result: Vec<&'a str> = (0..self.len())
+            .filter(|&id| !seen[id])
+            .map(|id| self.names[id])
+            .collect();
+        result.sort_unstable();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_is_reported() -> miette::Result<()> {
+        let graph = parse_graph("a: b\nb: c d\nc: e\nd: b\n")?;
+        let id = |name| graph.id(name).expect("name is in graph");
+        let error = graph
+            .route_order(id("a"), id("e"))
+            .expect_err("has a cycle");
+        assert_eq!(error.to_string(), "devices form a cycle: b -> d -> b");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycles_off_the_route_are_ignored() -> miette::Result<()> {
+        // x and y are not reachable from a, z cannot reach e and e -> a is
+        // after the end
+        let graph = parse_graph("a: b\nb: e z\nz: w\nw: z\nx: y\ny: x b\ne: a\n")?;
+        let id = |name| graph.id(name).expect("name is in graph");
+        let order = graph.route_order(id("a"), id("e"))?;
+        assert_eq!(order, [id("a"), id("b"), id("e")]);
+        assert!(graph.route_order(id("x"), id("e")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinks_and_unreachable() -> miette::Result<()> {
+        let graph = parse_graph("a: b c\nb: out\nx: c\n")?;
+        let id = |name| graph.id(name).expect("name is in graph");
+        assert_eq!(graph.outputs(id("out")), []);
+        assert_eq!(graph.outputs(id("a")), [id("b"), id("c")]);
+        assert_eq!(graph.inputs(id("c")), [id("a"), id("x")]);
+        assert_eq!(graph.unreachable_from(id("a")), ["x"]);
+        assert_eq!(graph.unreachable_from(id("b")), ["a", "c", "x"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_order() -> miette::Result<()> {
+        let graph = parse_graph("d: e\nb: d c\na: b\nc: d\n")?;
+        let id = |name| graph.id(name).expect("name is in graph");
+        let order = graph.route_order(id("a"), id("e"))?;
+        assert_eq!(order.len(), graph.len());
+        let mut position = vec![0; graph.len()];
+        for (i, &id) in order.iter().enumerate() {
+            position[id as usize] = i;
+        }
+        for id in 0..graph.len() as NodeId {
+            for &output in graph.outputs(id) {
+                assert!(position[id as usize] < position[output as usize]);
+            }
+        }
+        Ok(())
+    }
+}
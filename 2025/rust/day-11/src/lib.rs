@@ -1,2 +1,6 @@
+pub mod dot;
+pub mod graph;
 pub mod part1;
 pub mod part2;
+pub mod path_count;
+pub mod paths;
@@ -1,41 +1,21 @@
-use std::collections::{BTreeMap, VecDeque};
+use crate::{
+    graph::parse_graph,
+    path_count::{count_paths, warn_unreachable},
+};
+
+/// Device the paths start from
+pub const START: &str = "you";
+/// Device the paths end at
+pub const END: &str = "out";
 
 #[tracing::instrument]
-pub fn process(input: &'static str) -> miette::Result<String> {
-    let mut result = 0;
-    let graph = parse_graph(input);
-    let mut queue = VecDeque::new();
-    queue.push_back("you");
-    while let Some(next) = queue.pop_front() {
-        if next == "out" {
-            result += 1;
-            continue;
-        }
-        for &neighbour in graph.nodes.get(next).unwrap().iter() {
-            queue.push_back(neighbour);
-        }
-    }
+pub fn process(input: &str) -> miette::Result<String> {
+    let graph = parse_graph(input)?;
+    warn_unreachable(&graph, START);
+    let result = count_paths(&graph, START, END, &[])?;
     Ok(result.to_string())
 }
 
-fn parse_graph(input: &'static str) -> Graph {
-    let mut result = Graph::default();
-    for line in input.lines() {
-        let (node, neighbours) = line.split_once(":").unwrap();
-        let is_new = result
-            .nodes
-            .insert(node, neighbours.split_whitespace().collect())
-            .is_none();
-        debug_assert!(is_new);
-    }
-    result
-}
-
-#[derive(Debug, Default)]
-struct Graph {
-    nodes: BTreeMap<&'static str, Vec<&'static str>>,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use miette::{bail, miette};
+
+use crate::graph::{Graph, NodeId};
+
+/// Number of paths from `start` to `end` that pass through every node in
+/// `waypoints` (in any order).
+///
+/// Walks the graph in topological order keeping, for every node, how many
+/// paths from `start` reach it having seen each set of waypoints (as a bit
+/// mask) so every edge is only followed once per set.
+pub fn count_paths(
+    graph: &Graph,
+    start: &str,
+    end: &str,
+    waypoints: &[&str],
+) -> miette::Result<u64> {
+    let route = Route::new(graph, start, end, waypoints)?;
+    let ways = route.ways_from_start(graph)?;
+    Ok(ways[route.end as usize]
+        .get(&route.all_seen)
+        .copied()
+        .unwrap_or(0))
+}
+
+/// Logs a warning listing (some of) the devices that cannot be reached from
+/// `start`, if there are any
+pub fn warn_unreachable(graph: &Graph, start: &str) {
+    let Some(id) = graph.id(start) else {
+        return;
+    };
+    let unreachable = graph.unreachable_from(id);
+    if !unreachable.is_empty() {
+        const MAX_LISTED: usize = 10;
+        let listed = &unreachable[..unreachable.len().min(MAX_LISTED)];
+        let more = if unreachable.len() > MAX_LISTED {
+            " ..."
+        } else {
+            ""
+        };
+        tracing::warn!(
+            "{} devices are unreachable from {start:?}: {}{more}",
+            unreachable.len(),
+            listed.join(" ")
+        );
+    }
+}
+
+/// Number of paths counted by [`count_paths`] that use each edge (every edge
+/// of the graph as `(from, to, paths)` in order of `from`)
+pub fn count_paths_per_edge(
+    graph: &Graph,
+    start: &str,
+    end: &str,
+    waypoints: &[&str],
+) -> miette::Result<Vec<(NodeId, NodeId, u64)>> {
+    let route = Route::new(graph, start, end, waypoints)?;
+    let from_start = route.ways_from_start(graph)?;
+    let to_end = route.ways_to_end(graph)?;
+    let mut result = vec![];
+    for from in 0..graph.len() as NodeId {
+        for &to in graph.outputs(from) {
+            let mut paths = 0;
+            // Paths stop at the end
+            if from != route.end {
+                for (&before, &first) in from_start[from as usize].iter() {
+                    for (&after, &second) in to_end[to as usize].iter() {
+                        if before | after == route.all_seen {
+                            let through = first.checked_mul(second).ok_or_else(|| {
+                                miette!("too many paths to count (more than {})", u64::MAX)
+                            })?;
+                            add_paths(&mut paths, through)?;
+                        }
+                    }
+                }
+            }
+            result.push((from, to, paths));
+        }
+    }
+    Ok(result)
+}
+
+/// Start and end of the paths being counted with the waypoints as bits
+pub(crate) struct Route {
+    pub(crate) start: NodeId,
+    pub(crate) end: NodeId,
+    /// Nodes on some path from start to end (see [`Graph::route_order`])
+    pub(crate) order: Vec<NodeId>,
+    /// Bits of the waypoints at each node
+    pub(crate) waypoint_bits: Vec<u32>,
+    /// Mask with a bit set for every waypoint
+    pub(crate) all_seen: u32,
+}
+
+impl Route {
+    pub(crate) fn new(
+        graph: &Graph,
+        start: &str,
+        end: &str,
+        waypoints: &[&str],
+    ) -> miette::Result<Self> {
+        let id = |name: &str| match graph.id(name) {
+            Some(id) => Ok(id),
+            None => bail!("device {name:?} is not in the graph"),
+        };
+        let (start, end) = (id(start)?, id(end)?);
+        if waypoints.len() > u32::BITS as usize {
+            bail!(
+                "at most {} waypoints are supported but got {}",
+                u32::BITS,
+                waypoints.len()
+            );
+        }
+        let mut waypoint_bits = vec![0u32; graph.len()];
+        for (position, &waypoint) in waypoints.iter().enumerate() {
+            waypoint_bits[id(waypoint)? as usize] |= 1 << position;
+        }
+        Ok(Self {
+            start,
+            end,
+            order: graph.route_order(start, end)?,
+            waypoint_bits,
+            all_seen: ((1u64 << waypoints.len()) - 1) as u32,
+        })
+    }
+
+    /// Paths from the start to each node by the waypoints seen on the way
+    /// (including at both ends)
+    fn ways_from_start(&self, graph: &Graph) -> miette::Result<Vec<HashMap<u32, u64>>> {
+        let mut result: Vec<HashMap<u32, u64>> = vec![HashMap::new(); graph.len()];
+        result[self.start as usize].insert(self.waypoint_bits[self.start as usize], 1);
+        for &node in self.order.iter() {
+            // Paths stop at the end
+            if node == self.end || result[node as usize].is_empty() {
+                continue;
+            }
+            let current = result[node as usize].clone();
+            for &output in graph.outputs(node) {
+                for (&seen, &count) in current.iter() {
+                    add_paths(
+                        result[output as usize]
+                            .entry(seen | self.waypoint_bits[output as usize])
+                            .or_default(),
+                        count,
+                    )?;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Paths from each node to the end by the waypoints seen on the way
+    /// (including at both ends)
+    fn ways_to_end(&self, graph: &Graph) -> miette::Result<Vec<HashMap<u32, u64>>> {
+        let mut result: Vec<HashMap<u32, u64>> = vec![HashMap::new(); graph.len()];
+        result[self.end as usize].insert(self.waypoint_bits[self.end as usize], 1);
+        for &node in self.order.iter().rev() {
+            if node == self.end {
+                continue;
+            }
+            let mut ways = HashMap::new();
+            for &output in graph.outputs(node) {
+                for (&seen, &count) in result[output as usize].iter() {
+                    add_paths(
+                        ways.entry(seen | self.waypoint_bits[node as usize])
+                            .or_default(),
+                        count,
+                    )?;
+                }
+            }
+            result[node as usize] = ways;
+        }
+        Ok(result)
+    }
+}
+
+/// Adds `count` paths to `total` failing if there are more than fit in a
+/// `u64`
+fn add_paths(total: &mut u64, count: u64) -> miette::Result<()> {
+    *total = total
+        .checked_add(count)
+        .ok_or_else(|| miette!("too many paths to count (more than {})", u64::MAX))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::parse_graph;
+
+    #[test]
+    fn test_waypoints() -> miette::Result<()> {
+        let graph = parse_graph(
+            "a: b c
+b: d
+c: d
+d: e f
+e: g
+f: g
+",
+        )?;
+        assert_eq!(count_paths(&graph, "a", "g", &[])?, 4);
+        assert_eq!(count_paths(&graph, "a", "g", &["b"])?, 2);
+        assert_eq!(count_paths(&graph, "a", "g", &["c", "f"])?, 1);
+        assert_eq!(count_paths(&graph, "a", "g", &["b", "c"])?, 0);
+        assert_eq!(count_paths(&graph, "a", "g", &["a", "g"])?, 4);
+        assert_eq!(count_paths(&graph, "b", "d", &[])?, 1);
+        assert!(count_paths(&graph, "a", "z", &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_only_matters_on_route() -> miette::Result<()> {
+        let graph = parse_graph("a: b c\nb: d\nc: d x\nx: y z\ny: x\nd: e f\ne: b\n")?;
+        // x <-> y cannot reach d and d -> e -> b -> d only continues past d
+        assert_eq!(count_paths(&graph, "a", "d", &[])?, 2);
+        assert_eq!(count_paths(&graph, "x", "y", &[])?, 1);
+        assert!(count_paths(&graph, "a", "f", &[]).is_err());
+        assert!(count_paths(&graph, "c", "z", &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_paths_per_edge_add_up() -> miette::Result<()> {
+        let graph = parse_graph("a: b c\nb: d\nc: d e\nd: f\ne: f\nf: g\nx: f\n")?;
+        for waypoints in [&[][..], &["c"], &["d"], &["b", "e"]] {
+            let total = count_paths(&graph, "a", "f", waypoints)?;
+            let edges = count_paths_per_edge(&graph, "a", "f", waypoints)?;
+            // Every path leaves the start once and arrives at the end once
+            let id = |name| graph.id(name).expect("name is in graph");
+            let leaving: u64 = edges.iter().filter(|e| e.0 == id("a")).map(|e| e.2).sum();
+            let arriving: u64 = edges.iter().filter(|e| e.1 == id("f")).map(|e| e.2).sum();
+            assert_eq!((leaving, arriving), (total, total), "{waypoints:?}");
+            // Nothing continues past the end
+            assert!(edges.iter().all(|e| e.0 != id("f") || e.2 == 0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_doubling_chain() -> miette::Result<()> {
+        // Two ways to get from each node to the next so 2^60 paths in total
+        let input: String = (0..60)
+            .map(|i| format!("n{i}: l{i} r{i}\nl{i}: n{0}\nr{i}: n{0}\n", i + 1))
+            .collect();
+        let graph = parse_graph(&input)?;
+        assert_eq!(count_paths(&graph, "n0", "n60", &[])?, 1 << 60);
+        assert_eq!(count_paths(&graph, "n0", "n60", &["l7", "r59"])?, 1 << 58);
+        Ok(())
+    }
+
+    #[test]
+    fn test_too_many_paths() -> miette::Result<()> {
+        let input: String = (0..64)
+            .map(|i| format!("n{i}: l{i} r{i}\nl{i}: n{0}\nr{i}: n{0}\n", i + 1))
+            .collect();
+        let graph = parse_graph(&input)?;
+        assert_eq!(count_paths(&graph, "n0", "n63", &[])?, 1 << 63);
+        assert!(count_paths(&graph, "n0", "n64", &[]).is_err());
+        assert!(count_paths_per_edge(&graph, "n0", "n64", &[]).is_err());
+        Ok(())
+    }
+}
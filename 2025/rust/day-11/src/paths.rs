@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use crate::{
+    graph::{Graph, NodeId},
+    path_count::Route,
+};
+
+/// Lazily lists the paths from `start` to `end` that pass through every node
+/// in `waypoints` (in any order), stopping after `limit` paths if given.
+///
+/// Paths come out in depth first order following each device's outputs in
+/// the order they were listed. Branches that cannot finish at `end` with all
+/// the waypoints seen are never entered, so every step makes progress towards
+/// the next path.
+pub fn enumerate_paths<'g, 'a>(
+    graph: &'g Graph<'a>,
+    start: &str,
+    end: &str,
+    waypoints: &[&str],
+    limit: Option<usize>,
+) -> miette::Result<Paths<'g, 'a>> {
+    let route = Route::new(graph, start, end, waypoints)?;
+    let to_end = waypoints_to_end(graph, &route);
+    let mut result = Paths {
+        graph,
+        route,
+        to_end,
+        stack: vec![],
+        remaining: limit.unwrap_or(usize::MAX),
+    };
+    let (start, seen) = (
+        result.route.start,
+        result.route.waypoint_bits[result.route.start as usize],
+    );
+    if result.can_finish(start, seen) {
+        result.stack.push((start, 0, seen));
+    }
+    Ok(result)
+}
+
+/// Every set of waypoints (as a bit mask) seen on some path from each node to
+/// the end. Only whether a path exists matters here so unlike the counts in
+/// [`crate::path_count`] this cannot overflow however many paths there are.
+fn waypoints_to_end(graph: &Graph, route: &Route) -> Vec<HashSet<u32>> {
+    let mut result = vec![HashSet::new(); graph.len()];
+    result[route.end as usize].insert(route.waypoint_bits[route.end as usize]);
+    for &node in route.order.iter().rev() {
+        if node == route.end {
+            continue;
+        }
+        let own = route.waypoint_bits[node as usize];
+        let masks: HashSet<u32> = graph
+            .outputs(node)
+            .iter()
+            .flat_map(|&output| result[output as usize].iter().map(|after| after | own))
+            .collect();
+        result[node as usize] = masks;
+    }
+    result
+}
+
+/// Iterator returned by [`enumerate_paths`]
+pub struct Paths<'g, 'a> {
+    graph: &'g Graph<'a>,
+    route: Route,
+    /// Waypoints that can be seen on the way from each node to the end
+    to_end: Vec<HashSet<u32>>,
+    /// Path so far with the index of the next output to try from each node
+    /// and the waypoints seen up to it
+    stack: Vec<(NodeId, usize, u32)>,
+    remaining: usize,
+}
+
+impl Paths<'_, '_> {
+    /// Whether some path from `node` to the end sees the rest of the waypoints
+    fn can_finish(&self, node: NodeId, seen: u32) -> bool {
+        self.to_end[node as usize]
+            .iter()
+            .any(|after| seen | after == self.route.all_seen)
+    }
+}
+
+impl<'a> Iterator for Paths<'_, 'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let &mut (node, ref mut next_index, seen) = self.stack.last_mut()?;
+            if node == self.route.end {
+                // Only pushed if all the waypoints have been seen
+                let path = self
+                    .stack
+                    .iter()
+                    .map(|&(node, _, _)| self.graph.name(node))
+                    .collect();
+                self.stack.pop();
+                self.remaining -= 1;
+                return Some(path);
+            }
+            let Some(&output) = self.graph.outputs(node).get(*next_index) else {
+                self.stack.pop();
+                continue;
+            };
+            *next_index += 1;
+            let seen = seen | self.route.waypoint_bits[output as usize];
+            if self.can_finish(output, seen) {
+                self.stack.push((output, 0, seen));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, prelude::*};
+
+    use super::*;
+    use crate::{graph::parse_graph, path_count::count_paths};
+
+    #[test]
+    fn test_enumerate_paths() -> miette::Result<()> {
+        let graph = parse_graph("a: b c\nb: d\nc: d e\nd: f\ne: f\nx: a\n")?;
+        let paths: Vec<_> = enumerate_paths(&graph, "a", "f", &[], None)?.collect();
+        assert_eq!(
+            paths,
+            [
+                vec!["a", "b", "d", "f"],
+                vec!["a", "c", "d", "f"],
+                vec!["a", "c", "e", "f"]
+            ]
+        );
+        let paths: Vec<_> = enumerate_paths(&graph, "a", "f", &["c"], Some(1))?.collect();
+        assert_eq!(paths, [vec!["a", "c", "d", "f"]]);
+        assert_eq!(enumerate_paths(&graph, "a", "a", &[], None)?.count(), 1);
+        assert_eq!(enumerate_paths(&graph, "d", "e", &[], None)?.count(), 0);
+        assert!(enumerate_paths(&graph, "a", "z", &[], None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_more_paths_than_fit_in_u64() -> miette::Result<()> {
+        // Two ways from each node to the next so 2^70 paths in total
+        let input: String = (0..70)
+            .map(|i| format!("n{i}: l{i} r{i}\nl{i}: n{0}\nr{i}: n{0}\n", i + 1))
+            .collect();
+        let graph = parse_graph(&input)?;
+        assert!(count_paths(&graph, "n0", "n70", &[]).is_err());
+        let paths: Vec<_> = enumerate_paths(&graph, "n0", "n70", &["r69"], Some(2))?.collect();
+        assert_eq!(paths.len(), 2);
+        assert!(
+            paths
+                .iter()
+                .all(|path| path.len() == 141 && path[139] == "r69")
+        );
+        assert_ne!(paths[0], paths[1]);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        fn test_matches_counts_on_random_graphs(
+            (edges, waypoints, limit) in (2..=10usize).prop_flat_map(|len| {
+                (
+                    vec(vec(prop::bool::weighted(1.0 / 3.0), len), len),
+                    vec(0..len, 0..3),
+                    0..4usize,
+                )
+            })
+        ) {
+            // Edges only go to higher numbered nodes so there are no cycles
+            let len = edges.len();
+            let input: String = (0..len)
+                .map(|i| {
+                    let outputs: Vec<String> = (i + 1..len)
+                        .filter(|&j| edges[i][j])
+                        .map(|j| format!("n{j}"))
+                        .collect();
+                    format!("n{i}: {}\n", outputs.join(" "))
+                })
+                .collect();
+            let fail = |error: miette::Report| TestCaseError::fail(error.to_string());
+            let graph = parse_graph(&input).map_err(fail)?;
+            let names: Vec<String> = (0..len).map(|i| format!("n{i}")).collect();
+            let waypoints: Vec<&str> = waypoints.iter().map(|&i| names[i].as_str()).collect();
+            let (start, end) = (&names[0], &names[len - 1]);
+
+            let paths: Vec<_> = enumerate_paths(&graph, start, end, &waypoints, None)
+                .map_err(fail)?
+                .collect();
+            prop_assert_eq!(
+                paths.len() as u64,
+                count_paths(&graph, start, end, &waypoints).map_err(fail)?
+            );
+            for path in paths.iter() {
+                prop_assert_eq!(path.first(), Some(&start.as_str()));
+                prop_assert_eq!(path.last(), Some(&end.as_str()));
+                prop_assert!(waypoints.iter().all(|waypoint| path.contains(waypoint)));
+            }
+            prop_assert_eq!(
+                enumerate_paths(&graph, start, end, &waypoints, Some(limit))
+                    .map_err(fail)?
+                    .count(),
+                paths.len().min(limit)
+            );
+        }
+    }
+}